@@ -1,4 +1,6 @@
+use crate::{context::ParseOptions, lang::Headings};
 use clap::Parser;
+use gm_docs_parser::{GmLinkBase, GmNormalization, GmTextFormat};
 use std::path::PathBuf;
 
 /// A CLI intended for use by humans and machines to build GameMakerStudio 2 projects.
@@ -12,4 +14,165 @@ pub struct InputOpts {
     /// If set to true, then we won't emit anything. This basically makes it a linter for itself.
     #[clap(short, long)]
     pub do_not_emit: bool,
+
+    /// If set, every image referenced by an entry is copied from the input folder into this
+    /// folder, and links to images point to the copies instead of the online manual.
+    #[clap(long)]
+    pub assets_dir: Option<PathBuf>,
+
+    /// The language of the manual at `input_path`, which decides which words we look for in
+    /// the headings of each page.
+    #[clap(long, default_value = "en", value_parser = parse_lang)]
+    pub lang: String,
+
+    /// A localized copy of the manual to read descriptions from, written as `LANG=PATH`,
+    /// such as `fr=path/to/GMS2-Robohelp-fr`. This can be given several times.
+    #[clap(long, value_parser = parse_translation)]
+    pub translation: Vec<(String, PathBuf)>,
+
+    /// The format to write descriptions, examples and other text in: `markdown`, `plain` or
+    /// `html`.
+    #[clap(long, default_value = "markdown", value_parser = parse_text_format)]
+    pub text_format: GmTextFormat,
+
+    /// Also emit every description, example, return type and other piece of text as a
+    /// structured rich text document, alongside its rendered string.
+    #[clap(long)]
+    pub rich_text: bool,
+
+    /// Read every Markdown string back with a CommonMark parser, and report any whose text
+    /// doesn't match the text of the Html it came from.
+    #[clap(long)]
+    pub verify_markdown: bool,
+
+    /// Where links into the manual point: `legacy` for manual.yoyogames.com, `lts` or `monthly`
+    /// for that channel on manual.gamemaker.io, `local` or `local=PATH` for `file://` links into
+    /// a local mirror, `relative` for links relative to the root of the manual, or any other
+    /// Url, where `{lang}` stands in for the language.
+    #[clap(long, default_value = "legacy", value_parser = parse_link_base)]
+    pub link_base: GmLinkBase,
+
+    /// A step of cleaning up text to skip: `unicode-whitespace`, `smart-quotes`, `entities`,
+    /// `zero-width` or `repeated-whitespace`. This can be given several times.
+    #[clap(long, value_parser = parse_normalization_step)]
+    pub skip_normalization: Vec<NormalizationStep>,
+
+    /// The version of the manual. This is detected from the input if possible.
+    #[clap(long)]
+    pub manual_version: Option<String>,
+
+    /// The version of the IDE the manual shipped with. This is detected from the input if possible.
+    #[clap(long)]
+    pub ide_version: Option<String>,
+
+    /// The version of the runtime the manual shipped with. This is detected from the input if possible.
+    #[clap(long)]
+    pub runtime_version: Option<String>,
+}
+
+impl InputOpts {
+    pub fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            lang: self.lang.clone(),
+            link_base: self.link_base.clone(),
+            text_format: self.text_format,
+            normalization: self.normalization(),
+            assets_dir: self.assets_dir.clone(),
+            verify_markdown: self.verify_markdown,
+        }
+    }
+
+    /// How text should be cleaned up, leaving out every step we were asked to skip.
+    pub fn normalization(&self) -> GmNormalization {
+        let mut normalization = GmNormalization::default();
+        for step in self.skip_normalization.iter() {
+            let step = match step {
+                NormalizationStep::UnicodeWhitespace => &mut normalization.unicode_whitespace,
+                NormalizationStep::SmartQuotes => &mut normalization.smart_quotes,
+                NormalizationStep::Entities => &mut normalization.entities,
+                NormalizationStep::ZeroWidth => &mut normalization.zero_width,
+                NormalizationStep::RepeatedWhitespace => &mut normalization.repeated_whitespace,
+            };
+            *step = false;
+        }
+
+        normalization
+    }
+}
+
+/// The steps of [`GmNormalization`] which can be skipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormalizationStep {
+    UnicodeWhitespace,
+    SmartQuotes,
+    Entities,
+    ZeroWidth,
+    RepeatedWhitespace,
+}
+
+fn parse_normalization_step(arg: &str) -> Result<NormalizationStep, String> {
+    match arg {
+        "unicode-whitespace" => Ok(NormalizationStep::UnicodeWhitespace),
+        "smart-quotes" => Ok(NormalizationStep::SmartQuotes),
+        "entities" => Ok(NormalizationStep::Entities),
+        "zero-width" => Ok(NormalizationStep::ZeroWidth),
+        "repeated-whitespace" => Ok(NormalizationStep::RepeatedWhitespace),
+        _ => Err(format!(
+            "expected `unicode-whitespace`, `smart-quotes`, `entities`, `zero-width` or \
+            `repeated-whitespace`, but got `{}`",
+            arg
+        )),
+    }
+}
+
+fn parse_lang(arg: &str) -> Result<String, String> {
+    match Headings::for_lang(arg) {
+        Some(headings) => Ok(headings.lang.to_string()),
+        None => Err(format!(
+            "expected one of `{}`, but got `{}`",
+            Headings::langs().collect::<Vec<_>>().join("`, `"),
+            arg
+        )),
+    }
+}
+
+fn parse_link_base(arg: &str) -> Result<GmLinkBase, String> {
+    arg.parse()
+}
+
+fn parse_text_format(arg: &str) -> Result<GmTextFormat, String> {
+    match arg {
+        "markdown" => Ok(GmTextFormat::Markdown),
+        "plain" => Ok(GmTextFormat::Plain),
+        "html" => Ok(GmTextFormat::Html),
+        _ => Err(format!(
+            "expected `markdown`, `plain` or `html`, but got `{}`",
+            arg
+        )),
+    }
+}
+
+fn parse_translation(arg: &str) -> Result<(String, PathBuf), String> {
+    match arg.split_once('=') {
+        Some((lang, path)) if lang.is_empty() == false && path.is_empty() == false => {
+            Ok((parse_lang(lang)?, PathBuf::from(path)))
+        }
+        _ => Err(format!("expected `LANG=PATH`, but got `{}`", arg)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translations() {
+        assert_eq!(
+            parse_translation("FR=manuals/fr"),
+            Ok(("fr".to_string(), PathBuf::from("manuals/fr")))
+        );
+        assert!(parse_translation("xx=manuals/xx").is_err());
+        assert!(parse_translation("fr=").is_err());
+        assert!(parse_translation("manuals/fr").is_err());
+    }
 }
@@ -0,0 +1,148 @@
+use crate::context::ParseContext;
+use log::*;
+use std::path::{Component, Path, PathBuf};
+
+/// Gives the link an image should be written with, for an image `src` on a page in `directory`.
+/// Normally, that's the image's Url in the online manual, but if we're exporting assets, we copy
+/// the image over and link to the copy instead, unless it can't be copied. Images outside of the
+/// manual keep their `src`.
+pub fn image_src(cx: &ParseContext, directory: &Path, src: &str) -> String {
+    let path = normalize_path(&directory.join(src));
+    let online_src = || match cx.try_convert_to_url(&path) {
+        Some(url) => url.to_string(),
+        None => {
+            warn!(
+                "image {:?} is outside of the manual, so we kept its src",
+                path
+            );
+            src.to_string()
+        }
+    };
+
+    let assets_dir = match &cx.options.assets_dir {
+        Some(v) => v,
+        None => return online_src(),
+    };
+
+    // the manual can be given as `./manual` or through a link, so we compare real paths
+    let relative = match canonical_path(&path).strip_prefix(canonical_path(&cx.base_path)) {
+        Ok(v) => v.to_owned(),
+        Err(_) => {
+            warn!(
+                "image {:?} is outside of the manual, so we kept its src",
+                path
+            );
+            return src.to_string();
+        }
+    };
+    let destination = assets_dir.join(&relative);
+
+    let copied = cx.copy_once(&path, || {
+        let copied = destination
+            .parent()
+            .map(std::fs::create_dir_all)
+            .unwrap_or(Ok(()))
+            .and_then(|_| std::fs::copy(&path, &destination));
+
+        match copied {
+            Ok(_) => true,
+            Err(e) => {
+                error!(
+                    "couldn't copy image {:?} to {:?}, so we linked to the manual: {}",
+                    path, destination, e
+                );
+                false
+            }
+        }
+    });
+
+    if copied {
+        destination.to_string_lossy().replace('\\', "/")
+    } else {
+        online_src()
+    }
+}
+
+/// The real path of a file, or, if it doesn't exist, the path with its `.` and `..` resolved.
+fn canonical_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| normalize_path(path))
+}
+
+/// Resolves any `.` and `..` in a path without touching the file system.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut output = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if output.pop() == false {
+                    output.push(component);
+                }
+            }
+            other => output.push(other),
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize() {
+        assert_eq!(
+            normalize_path(Path::new(
+                "data/GameMaker_Language/GML_Reference/../../assets/./a.png"
+            )),
+            Path::new("data/assets/a.png")
+        );
+    }
+
+    #[test]
+    fn image_sources() {
+        let cx = ParseContext::new(PathBuf::from("manual"), Default::default());
+        let directory = Path::new("manual/Drawing");
+
+        assert_eq!(
+            image_src(&cx, directory, "../assets/a.png"),
+            "https://manual.yoyogames.com/assets/a.png"
+        );
+        assert_eq!(image_src(&cx, directory, "/abs/pic.png"), "/abs/pic.png");
+    }
+
+    #[test]
+    fn copies_images() {
+        let root =
+            std::env::temp_dir().join(format!("gm-docs-parser-assets-{}", std::process::id()));
+        let manual = root.join("manual");
+        let out = root.join("out");
+        std::fs::create_dir_all(manual.join("Drawing")).unwrap();
+        std::fs::create_dir_all(manual.join("assets")).unwrap();
+        std::fs::write(manual.join("assets/a.png"), b"png").unwrap();
+
+        let cx = ParseContext::new(
+            manual.join("."),
+            crate::context::ParseOptions {
+                assets_dir: Some(out.clone()),
+                ..Default::default()
+            },
+        );
+        let directory = manual.join("Drawing");
+
+        let copied = image_src(&cx, &directory, "../assets/a.png");
+        assert_eq!(Path::new(&copied), out.join("assets/a.png"));
+        assert_eq!(std::fs::read(out.join("assets/a.png")).unwrap(), b"png");
+
+        // an image which can't be copied is linked to where it is instead
+        let missing = image_src(&cx, &directory, "../assets/missing.png");
+        assert!(missing.ends_with("/assets/missing.png"), "{}", missing);
+        assert!(missing.starts_with("https://") || missing.starts_with("file://"));
+        assert_eq!(image_src(&cx, &directory, "../assets/missing.png"), missing);
+        assert!(out.join("assets/missing.png").exists() == false);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,214 @@
+use crate::assets::normalize_path;
+use gm_docs_parser::{GmImage, GmLinkBase, GmNormalization, GmRichText, GmTextFormat};
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+use url::Url;
+
+/// How a manual should be read and written out.
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// The language of the manual, which decides where links point for some sites.
+    pub lang: String,
+    /// Where links into the manual point.
+    pub link_base: GmLinkBase,
+    /// The format every text field is written in.
+    pub text_format: GmTextFormat,
+    /// How every piece of text is cleaned up once it's read.
+    pub normalization: GmNormalization,
+    /// If set, images are copied into this directory, and links to images point here.
+    pub assets_dir: Option<PathBuf>,
+    /// If set, every Markdown string we write is read back and checked against its Html.
+    pub verify_markdown: bool,
+}
+
+/// Problems found while parsing, which are reported once the whole manual is parsed.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    /// Every page which wasn't UTF-8, and the encoding we read it as.
+    pub transcoded: BTreeMap<PathBuf, &'static str>,
+    /// The number of Markdown strings which didn't match their Html.
+    pub verify_failures: usize,
+}
+
+impl Diagnostics {
+    /// Adds the problems found while parsing another manual, such as a translation.
+    pub fn append(&mut self, other: Diagnostics) {
+        self.transcoded.extend(other.transcoded);
+        self.verify_failures += other.verify_failures;
+    }
+}
+
+/// Everything we need to know about the manual we're parsing, and everything we collect along
+/// the way. Each manual gets a context of its own, so several can be parsed side by side.
+#[derive(Debug)]
+pub struct ParseContext {
+    /// The folder of the manual, which links and sources are made relative to.
+    pub base_path: PathBuf,
+    /// The site which links into the manual point to.
+    pub url_base: Url,
+    pub options: ParseOptions,
+    pub diagnostics: RefCell<Diagnostics>,
+    /// The reference pages which document a single entry, by their path in the manual, and the
+    /// name of that entry.
+    pub entry_pages: BTreeMap<PathBuf, String>,
+    /// Every image we've come across since the last call to [`take_images`].
+    ///
+    /// [`take_images`]: #method.take_images
+    images: RefCell<Vec<GmImage>>,
+    /// Every tooltip term we've come across, and its definition.
+    glossary: RefCell<BTreeMap<String, String>>,
+    /// Every image we've already tried to copy, and whether it was copied, so we don't copy the
+    /// same image for every page it's on.
+    copied: RefCell<BTreeMap<PathBuf, bool>>,
+}
+
+impl ParseContext {
+    pub fn new(base_path: PathBuf, options: ParseOptions) -> Self {
+        let url_base = options
+            .link_base
+            .base_url(&options.lang, &base_path)
+            .unwrap_or_else(|| panic!("couldn't make links from {:?}", options.link_base));
+
+        Self {
+            base_path,
+            url_base,
+            options,
+            diagnostics: Default::default(),
+            entry_pages: Default::default(),
+            images: Default::default(),
+            glossary: Default::default(),
+            copied: Default::default(),
+        }
+    }
+
+    /// A context for a localized copy of this manual at `root`. It links to the same site, so
+    /// its entries can be matched up with ours by their links.
+    pub fn for_translation(&self, root: &Path) -> Self {
+        Self {
+            url_base: self.url_base.clone(),
+            entry_pages: self.entry_pages.clone(),
+            ..Self::new(root.to_owned(), self.options.clone())
+        }
+    }
+
+    pub fn text_format(&self) -> GmTextFormat {
+        self.options.text_format
+    }
+
+    /// Renders text in the format we're writing. Links into the manual are trusted, even
+    /// when they point somewhere Html normally wouldn't allow, like a local mirror.
+    pub fn render(&self, text: &GmRichText) -> String {
+        text.render_with_link_base(self.text_format(), &self.url_base)
+    }
+
+    /// Cleans up text which was read straight out of the Html, rather than through
+    /// [`Markdown`].
+    ///
+    /// [`Markdown`]: ../markdown/struct.Markdown.html
+    pub fn normalize(&self, txt: &str) -> String {
+        self.options.normalization.normalize(txt)
+    }
+
+    /// Takes every image found since the last call. Call this before converting an entry to
+    /// clear out images from anything else.
+    pub fn take_images(&self) -> Vec<GmImage> {
+        std::mem::take(&mut *self.images.borrow_mut())
+    }
+
+    pub fn add_image(&self, image: GmImage) {
+        self.images.borrow_mut().push(image);
+    }
+
+    /// Takes every glossary term found so far.
+    pub fn take_glossary(&self) -> BTreeMap<String, String> {
+        std::mem::take(&mut *self.glossary.borrow_mut())
+    }
+
+    /// Records a glossary term, unless we already have a definition for it.
+    pub fn add_glossary_term(&self, term: String, definition: String) {
+        self.glossary.borrow_mut().entry(term).or_insert(definition);
+    }
+
+    /// Copies an image with `copy`, unless we've already tried to, and tells us if the image
+    /// was copied.
+    pub fn copy_once(&self, path: &Path, copy: impl FnOnce() -> bool) -> bool {
+        if let Some(&copied) = self.copied.borrow().get(path) {
+            return copied;
+        }
+
+        let copied = copy();
+        self.copied.borrow_mut().insert(path.to_owned(), copied);
+        copied
+    }
+
+    /// Finds the entry which a page documents, if it documents one.
+    pub fn entry_for_page(&self, fpath: &Path) -> Option<String> {
+        self.entry_pages.get(&self.relative_path(fpath)?).cloned()
+    }
+
+    /// The path of a file in the manual, relative to the folder of the manual.
+    fn relative_path(&self, fpath: &Path) -> Option<PathBuf> {
+        let path = normalize_path(fpath);
+        Some(
+            path.strip_prefix(normalize_path(&self.base_path))
+                .ok()?
+                .to_owned(),
+        )
+    }
+
+    pub fn convert_to_url(&self, path_to_strip: &Path) -> Url {
+        self.try_convert_to_url(path_to_strip)
+            .unwrap_or_else(|| panic!("{:?} is outside of the manual", path_to_strip))
+    }
+
+    /// Converts a path to its Url in the online manual, or wherever else links point, unless
+    /// the path is outside of the manual.
+    pub fn try_convert_to_url(&self, path_to_strip: &Path) -> Option<Url> {
+        let output = self.relative_path(path_to_strip)?;
+
+        self.url_base
+            .join(&output.to_str()?.replace('\\', "/"))
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn side_by_side() {
+        let first = ParseContext::new(PathBuf::from("a/manual"), Default::default());
+        let second = ParseContext::new(PathBuf::from("b/manual"), Default::default());
+        let page = Path::new("Drawing/draw_set_colour.htm");
+
+        assert_eq!(
+            first.convert_to_url(&first.base_path.join(page)),
+            second.convert_to_url(&second.base_path.join(page))
+        );
+        assert_eq!(second.try_convert_to_url(&first.base_path.join(page)), None);
+    }
+
+    #[test]
+    fn translated_entry_pages() {
+        let mut primary = ParseContext::new(PathBuf::from("manual"), Default::default());
+        primary.entry_pages = BTreeMap::from([(PathBuf::from("Maths/abs.htm"), "abs".to_string())]);
+        let translation = primary.for_translation(Path::new("translations/fr"));
+
+        assert_eq!(
+            primary.entry_for_page(Path::new("manual/Maths/abs.htm")),
+            Some("abs".to_string())
+        );
+        assert_eq!(
+            translation.entry_for_page(Path::new("translations/fr/Maths/./abs.htm")),
+            Some("abs".to_string())
+        );
+        assert_eq!(
+            translation.entry_for_page(Path::new("manual/Maths/abs.htm")),
+            None
+        );
+    }
+}
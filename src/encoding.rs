@@ -0,0 +1,98 @@
+use crate::context::ParseContext;
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+use log::*;
+use std::path::Path;
+
+/// Reads a page of the manual, whatever its encoding. Older and localized manuals have pages in
+/// Windows-1252 and UTF-16, so we go by the byte order mark, then the `<meta charset>`, and fall
+/// back to Windows-1252 if the page isn't valid UTF-8. Pages which weren't UTF-8 are recorded in
+/// the diagnostics.
+pub fn read_page(cx: &ParseContext, path: &Path) -> std::io::Result<String> {
+    let bytes = std::fs::read(path)?;
+    let (txt, encoding, had_errors) = decode(&bytes);
+
+    if encoding != UTF_8 {
+        cx.diagnostics
+            .borrow_mut()
+            .transcoded
+            .insert(path.to_owned(), encoding.name());
+    }
+    if had_errors {
+        warn!(
+            "{:?} isn't valid {}, so some characters were replaced",
+            path,
+            encoding.name()
+        );
+    }
+
+    Ok(txt)
+}
+
+fn decode(bytes: &[u8]) -> (String, &'static Encoding, bool) {
+    if let Some((encoding, bom_length)) = Encoding::for_bom(bytes) {
+        let (txt, had_errors) = encoding.decode_without_bom_handling(&bytes[bom_length..]);
+        return (txt.into_owned(), encoding, had_errors);
+    }
+
+    // a page can't really be UTF-16 without a byte order mark, so its meta tag is wrong
+    let declared = meta_charset(bytes).map(|encoding| encoding.output_encoding());
+    if let Some(encoding) = declared.filter(|&encoding| encoding != UTF_8) {
+        let (txt, had_errors) = encoding.decode_without_bom_handling(bytes);
+        return (txt.into_owned(), encoding, had_errors);
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(txt) => (txt.to_string(), UTF_8, false),
+        Err(_) => {
+            let (txt, had_errors) = WINDOWS_1252.decode_without_bom_handling(bytes);
+            (txt.into_owned(), WINDOWS_1252, had_errors)
+        }
+    }
+}
+
+/// Finds the encoding a page declares, with either `<meta charset="...">` or
+/// `<meta http-equiv="Content-Type" content="text/html; charset=...">`. Like a browser, we only
+/// look at the start of the page.
+fn meta_charset(bytes: &[u8]) -> Option<&'static Encoding> {
+    let head = &bytes[..bytes.len().min(1024)];
+    let head = String::from_utf8_lossy(head).to_ascii_lowercase();
+
+    head.match_indices("<meta").find_map(|(start, _)| {
+        let tag = &head[start..];
+        let tag = &tag[..tag.find('>')?];
+        let label = &tag[tag.find("charset")? + "charset".len()..];
+        let label = label.trim_start().strip_prefix('=')?.trim_start();
+        let label = label.trim_start_matches(['"', '\'']);
+        let end = label
+            .find(|c: char| c == '"' || c == '\'' || c == ';' || c.is_ascii_whitespace())
+            .unwrap_or(label.len());
+
+        Encoding::for_label(&label.as_bytes()[..end])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodings() {
+        let utf16: Vec<u8> = [0xff, 0xfe]
+            .into_iter()
+            .chain("<p>é</p>".encode_utf16().flat_map(|c| c.to_le_bytes()))
+            .collect();
+        assert_eq!(decode(&utf16).0, "<p>é</p>");
+
+        let declared = b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=windows-1252\"><p>\x93hi\x94</p>";
+        assert_eq!(
+            decode(declared).0,
+            "<meta http-equiv=\"Content-Type\" content=\"text/html; charset=windows-1252\"><p>\u{201c}hi\u{201d}</p>"
+        );
+
+        let undeclared = decode(b"<meta charset='utf-8'><p>caf\xe9</p>");
+        assert_eq!(undeclared.0, "<meta charset='utf-8'><p>caf\u{e9}</p>");
+        assert_eq!(undeclared.1, WINDOWS_1252);
+
+        assert_eq!(decode("<p>café</p>".as_bytes()).1, UTF_8);
+    }
+}
@@ -0,0 +1,312 @@
+/// The words used in the headings and table headers of manual pages, which we use to find each
+/// section and column. Each heading can have several spellings, and matches if it contains any
+/// of them.
+#[derive(Debug)]
+pub struct Headings {
+    pub lang: &'static str,
+    pub syntax: &'static [&'static str],
+    pub returns: &'static [&'static str],
+    pub example: &'static [&'static str],
+    pub argument: &'static [&'static str],
+    pub constant: &'static [&'static str],
+    pub description: &'static [&'static str],
+    /// Not a heading, but the word used in argument descriptions to mark them as optional.
+    pub optional: &'static [&'static str],
+    /// The first header of a table of struct members.
+    pub member: &'static [&'static str],
+    /// The first header of a table of variables on an overview page. Other tables start with
+    /// the same word, like the functions which work on a variable, so these match exactly.
+    pub variable: &'static [&'static str],
+    /// The header of a column giving the type of a member, variable or shader builtin.
+    pub data_type: &'static [&'static str],
+    /// The first header of a table of error messages.
+    pub error: &'static [&'static str],
+    /// The header of the column explaining an error, besides the description ones.
+    pub explanation: &'static [&'static str],
+    /// The header of the column of symbols in a table of operators.
+    pub operator: &'static [&'static str],
+    /// The header of the column of precedences in a table of operators.
+    pub precedence: &'static [&'static str],
+}
+
+impl Headings {
+    /// Finds the headings for a language code, like `en` or `fr`.
+    pub fn for_lang(lang: &str) -> Option<&'static Headings> {
+        HEADINGS.iter().find(|h| h.lang.eq_ignore_ascii_case(lang))
+    }
+
+    /// Every language code we know the headings of.
+    pub fn langs() -> impl Iterator<Item = &'static str> {
+        HEADINGS.iter().map(|h| h.lang)
+    }
+
+    #[cfg(test)]
+    pub fn english() -> &'static Headings {
+        &HEADINGS[0]
+    }
+
+    /// Checks if some heading text contains any of the given words.
+    pub fn matches(words: &[&str], heading: &str) -> bool {
+        let heading = heading.to_lowercase();
+        words.iter().any(|word| heading.contains(word))
+    }
+
+    /// Checks if some heading text is exactly one of the given words, other than its case and
+    /// the whitespace around it.
+    pub fn is_exactly(words: &[&str], heading: &str) -> bool {
+        let heading = heading.trim().to_lowercase();
+        words.contains(&heading.as_str())
+    }
+}
+
+/// Every language we know the headings of. These are all lowercase, and English is always first.
+static HEADINGS: [Headings; 11] = [
+    Headings {
+        lang: "en",
+        syntax: &["syntax"],
+        returns: &["returns"],
+        example: &["example"],
+        argument: &["argument"],
+        constant: &["constant"],
+        description: &["description"],
+        optional: &["optional"],
+        member: &["member", "variable", "property"],
+        variable: &[
+            "variable",
+            "variables",
+            "variable name",
+            "built-in variable",
+        ],
+        data_type: &["type"],
+        error: &["error", "message"],
+        explanation: &["explanation", "meaning"],
+        operator: &["operator"],
+        precedence: &["precedence"],
+    },
+    Headings {
+        lang: "fr",
+        syntax: &["syntaxe"],
+        returns: &["renvoie", "retourne", "valeur de retour"],
+        example: &["exemple"],
+        argument: &["argument"],
+        constant: &["constante"],
+        description: &["description"],
+        optional: &["optionnel", "facultatif"],
+        member: &["membre", "variable", "propriété"],
+        variable: &["variable", "variables", "nom de la variable"],
+        data_type: &["type"],
+        error: &["erreur", "message"],
+        explanation: &["explication", "signification"],
+        operator: &["opérateur"],
+        precedence: &["priorité", "précédence"],
+    },
+    Headings {
+        lang: "de",
+        syntax: &["syntax"],
+        returns: &["rückgabe", "gibt zurück"],
+        example: &["beispiel"],
+        argument: &["argument"],
+        constant: &["konstante"],
+        description: &["beschreibung"],
+        optional: &["optional"],
+        member: &["mitglied", "variable", "eigenschaft"],
+        variable: &["variable", "variablen", "variablenname"],
+        data_type: &["typ"],
+        error: &["fehler", "meldung"],
+        explanation: &["erklärung", "bedeutung"],
+        operator: &["operator"],
+        precedence: &["rangfolge", "priorität", "vorrang"],
+    },
+    Headings {
+        lang: "es",
+        syntax: &["sintaxis"],
+        returns: &["devuelve", "retorna"],
+        example: &["ejemplo"],
+        argument: &["argumento"],
+        constant: &["constante"],
+        description: &["descripción"],
+        optional: &["opcional"],
+        member: &["miembro", "variable", "propiedad"],
+        variable: &["variable", "variables", "nombre de la variable"],
+        data_type: &["tipo"],
+        error: &["error", "mensaje"],
+        explanation: &["explicación", "significado"],
+        operator: &["operador"],
+        precedence: &["precedencia", "prioridad"],
+    },
+    Headings {
+        lang: "it",
+        syntax: &["sintassi"],
+        returns: &["restituisce", "ritorna"],
+        example: &["esempio"],
+        argument: &["argomento"],
+        constant: &["costante"],
+        description: &["descrizione"],
+        optional: &["opzionale", "facoltativo"],
+        member: &["membro", "variabile", "proprietà"],
+        variable: &["variabile", "variabili", "nome della variabile"],
+        data_type: &["tipo"],
+        error: &["errore", "messaggio"],
+        explanation: &["spiegazione", "significato"],
+        operator: &["operatore"],
+        precedence: &["precedenza", "priorità"],
+    },
+    Headings {
+        lang: "pt",
+        syntax: &["sintaxe"],
+        returns: &["retorna", "devolve"],
+        example: &["exemplo"],
+        argument: &["argumento"],
+        constant: &["constante"],
+        description: &["descrição"],
+        optional: &["opcional"],
+        member: &["membro", "variável", "propriedade"],
+        variable: &["variável", "variáveis", "nome da variável"],
+        data_type: &["tipo"],
+        error: &["erro", "mensagem"],
+        explanation: &["explicação", "significado"],
+        operator: &["operador"],
+        precedence: &["precedência", "prioridade"],
+    },
+    Headings {
+        lang: "ru",
+        syntax: &["синтаксис"],
+        returns: &["возвращает"],
+        example: &["пример"],
+        argument: &["аргумент"],
+        constant: &["константа"],
+        description: &["описание"],
+        optional: &["необязательн"],
+        member: &["член", "переменн", "свойств"],
+        variable: &["переменная", "переменные", "имя переменной"],
+        data_type: &["тип"],
+        error: &["ошибк", "сообщени"],
+        explanation: &["объяснени", "значени"],
+        operator: &["оператор"],
+        precedence: &["приоритет"],
+    },
+    Headings {
+        lang: "pl",
+        syntax: &["składnia"],
+        returns: &["zwraca"],
+        example: &["przykład"],
+        argument: &["argument"],
+        constant: &["stała"],
+        description: &["opis"],
+        optional: &["opcjonaln"],
+        member: &["składow", "zmienn", "właściwoś"],
+        variable: &["zmienna", "zmienne", "nazwa zmiennej"],
+        data_type: &["typ"],
+        error: &["błąd", "komunikat"],
+        explanation: &["wyjaśnienie", "znaczenie"],
+        operator: &["operator"],
+        precedence: &["priorytet", "kolejność"],
+    },
+    Headings {
+        lang: "ja",
+        syntax: &["構文"],
+        returns: &["戻り値"],
+        example: &["例"],
+        argument: &["引数"],
+        constant: &["定数"],
+        description: &["説明"],
+        optional: &["省略可能", "オプション"],
+        member: &["メンバー", "変数", "プロパティ"],
+        variable: &["変数", "変数名"],
+        data_type: &["型", "タイプ"],
+        error: &["エラー", "メッセージ"],
+        explanation: &["意味"],
+        operator: &["演算子"],
+        precedence: &["優先順位", "優先度"],
+    },
+    Headings {
+        lang: "zh",
+        syntax: &["语法"],
+        returns: &["返回"],
+        example: &["例子", "示例"],
+        argument: &["参数"],
+        constant: &["常量"],
+        description: &["描述", "说明"],
+        optional: &["可选"],
+        member: &["成员", "变量", "属性"],
+        variable: &["变量", "变量名"],
+        data_type: &["类型"],
+        error: &["错误", "消息"],
+        explanation: &["解释", "含义"],
+        operator: &["运算符"],
+        precedence: &["优先级"],
+    },
+    Headings {
+        lang: "ko",
+        syntax: &["구문"],
+        returns: &["반환"],
+        example: &["예제", "예시"],
+        argument: &["인수"],
+        constant: &["상수"],
+        description: &["설명"],
+        optional: &["선택"],
+        member: &["멤버", "변수", "속성"],
+        variable: &["변수", "변수 이름"],
+        data_type: &["유형", "타입"],
+        error: &["오류", "메시지"],
+        explanation: &["의미"],
+        operator: &["연산자"],
+        precedence: &["우선순위"],
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn languages() {
+        assert_eq!(Headings::english().lang, "en");
+        assert_eq!(Headings::for_lang("FR").map(|h| h.lang), Some("fr"));
+        assert!(Headings::for_lang("xx").is_none());
+        assert_eq!(Headings::langs().count(), HEADINGS.len());
+    }
+
+    #[test]
+    fn every_word_is_lowercase() {
+        for headings in HEADINGS.iter() {
+            let words = [
+                headings.syntax,
+                headings.returns,
+                headings.example,
+                headings.argument,
+                headings.constant,
+                headings.description,
+                headings.optional,
+                headings.member,
+                headings.variable,
+                headings.data_type,
+                headings.error,
+                headings.explanation,
+                headings.operator,
+                headings.precedence,
+            ];
+
+            for words in words {
+                assert!(
+                    words.is_empty() == false,
+                    "`{}` is missing words",
+                    headings.lang
+                );
+                for word in words {
+                    assert_eq!(word.to_lowercase(), *word, "`{}`", headings.lang);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn matching() {
+        let french = Headings::for_lang("fr").unwrap();
+
+        assert!(Headings::matches(french.description, "Description :"));
+        assert!(Headings::matches(french.optional, "valeur [Optionnel]"));
+        assert!(Headings::is_exactly(french.variable, " Variables "));
+        assert!(Headings::is_exactly(french.variable, "Fonctions de variable") == false);
+    }
+}
@@ -13,21 +13,151 @@
 //! documentation makes are held up by invariants in the `exe` which creates the Json,
 //! rather than the type system itself.
 
-use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use url::Url;
 
+mod link_base;
+mod normalize;
+mod rich_text;
+pub use link_base::*;
+pub use normalize::*;
+pub use rich_text::*;
+
+/// A language code, such as `en` or `fr`.
+pub type Lang = String;
+
+/// The version of the shape of [`GmManual`]'s Json. This changes whenever the typings do, and
+/// deserializing a [`GmManual`] with a different `schemaVersion` fails, rather than silently
+/// dropping or defaulting fields.
+///
+/// [`GmManual`]: struct.GmManual.html
+pub const SCHEMA_VERSION: u32 = 14;
+
 /// The typings for the Entire Manual. This can be read as one massive Json.
-#[derive(Debug, Default, Clone, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct GmManual {
+    /// The [`SCHEMA_VERSION`] this manual was written with. Manuals from before the keys were
+    /// camelCase wrote this as `schema_version`, which is still read so they fail with the
+    /// right error.
+    ///
+    /// [`SCHEMA_VERSION`]: constant.SCHEMA_VERSION.html
+    #[serde(
+        alias = "schema_version",
+        deserialize_with = "deserialize_schema_version"
+    )]
+    pub schema_version: u32,
+    /// Where and when this manual was generated.
+    pub metadata: GmManualMetadata,
+    /// The language of the descriptions in this manual. Descriptions in other languages
+    /// are kept in each entry's `descriptions`.
+    #[serde(default = "default_lang")]
+    pub language: Lang,
+    /// The format of every description, example and other text in this manual.
+    #[serde(default)]
+    pub text_format: GmTextFormat,
     /// The built in functions within the manual created by Yyg.
     pub functions: BTreeMap<String, GmManualFunction>,
+    /// The built in constructors within the manual created by Yyg, which are called with `new`.
+    #[serde(default)]
+    pub constructors: BTreeMap<String, GmManualConstructor>,
     /// The built in variables within the manual created by Yyg.
     pub variables: BTreeMap<String, GmManualVariable>,
     /// Many of the built in constants within the manual created by Yyg. Constants are
     /// difficult to accurately scrape from the documentation, so there will be missing
     /// constants as the scrapper gets better and better at finding them.
     pub constants: BTreeMap<String, GmManualConstant>,
+    /// The built-in enums, whose members are found in `constants`.
+    #[serde(default)]
+    pub enums: BTreeMap<String, GmManualEnum>,
+    /// The operators and accessors of GML, keyed by their symbol.
+    #[serde(default)]
+    pub operators: BTreeMap<String, GmManualOperator>,
+    /// The uniforms, attributes and constants which GameMaker provides to shaders.
+    #[serde(default)]
+    pub shader_builtins: BTreeMap<String, GmManualShaderBuiltin>,
+    /// The compiler and runner error messages explained in the Manual, keyed by their pattern.
+    /// Use [`GmManual::find_error`] to look up a concrete error message.
+    ///
+    /// [`GmManual::find_error`]: struct.GmManual.html#method.find_error
+    #[serde(default)]
+    pub errors: BTreeMap<String, GmManualError>,
+    /// Terms which the Manual defines in tooltips, and their definitions. Descriptions mark
+    /// these terms with a [`GlossaryTerm`], whose key can be looked up here.
+    ///
+    /// [`GlossaryTerm`]: enum.GmInline.html#variant.GlossaryTerm
+    #[serde(default)]
+    pub glossary: BTreeMap<String, String>,
+}
+
+impl Default for GmManual {
+    fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            metadata: Default::default(),
+            language: default_lang(),
+            text_format: Default::default(),
+            functions: Default::default(),
+            constructors: Default::default(),
+            variables: Default::default(),
+            constants: Default::default(),
+            enums: Default::default(),
+            operators: Default::default(),
+            shader_builtins: Default::default(),
+            errors: Default::default(),
+            glossary: Default::default(),
+        }
+    }
+}
+
+fn default_lang() -> Lang {
+    "en".to_string()
+}
+
+fn deserialize_schema_version<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    let version = u32::deserialize(deserializer)?;
+
+    if version == SCHEMA_VERSION {
+        Ok(version)
+    } else {
+        Err(serde::de::Error::custom(format!(
+            "this manual has schema version {}, but this version of gm-docs-parser reads schema version {}",
+            version, SCHEMA_VERSION
+        )))
+    }
+}
+
+/// Information on how a [`GmManual`] was generated. Versions which couldn't be detected
+/// from the input, and weren't given to the parser, are `None`.
+///
+/// [`GmManual`]: struct.GmManual.html
+#[derive(Debug, Default, Clone, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GmManualMetadata {
+    /// The version of the Manual which was parsed.
+    pub manual_version: Option<String>,
+
+    /// The version of the IDE the Manual shipped with.
+    pub ide_version: Option<String>,
+
+    /// The version of the runtime the Manual shipped with.
+    pub runtime_version: Option<String>,
+
+    /// The version of gm-docs-parser which generated the manual.
+    pub parser_version: String,
+
+    /// When the manual was generated, in seconds since the Unix epoch.
+    pub generated_at: u64,
+
+    /// The path of the folder the manual was generated from.
+    pub input_path: String,
+
+    /// The Url which every link into the Manual starts with, as chosen with a [`GmLinkBase`].
+    ///
+    /// [`GmLinkBase`]: enum.GmLinkBase.html
+    #[serde(default)]
+    pub link_base: String,
 }
 
 /// A function scraped from the Gm Manual.
@@ -47,17 +177,152 @@ pub struct GmManualFunction {
     /// are `ds_list_add`, where users can invoke it as `ds_list_add(list, index, 1, 2, 3, 4 /* etc */);`
     pub is_variadic: bool,
 
+    /// How `required_parameters` and `is_variadic` were worked out, since the Manual never
+    /// states them directly.
+    #[serde(default)]
+    pub inference: GmParameterInference,
+
     /// The example given in the Manual.
     pub example: String,
 
+    /// The example as a [`GmRichText`]. This is only present if the parser was asked for it.
+    ///
+    /// [`GmRichText`]: struct.GmRichText.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rich_example: Option<GmRichText>,
+
     /// The description of what the function does.
     pub description: String,
 
+    /// The description as a [`GmRichText`], which can be rendered as something other than
+    /// Markdown. This is only present if the parser was asked for it.
+    ///
+    /// [`GmRichText`]: struct.GmRichText.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rich_description: Option<GmRichText>,
+
+    /// The description in every other language ingested, keyed by language.
+    #[serde(default)]
+    pub descriptions: BTreeMap<Lang, String>,
+
     /// What the function returns.
     pub returns: String,
 
+    /// What the function returns as a [`GmRichText`]. This is only present if the parser was asked for it.
+    ///
+    /// [`GmRichText`]: struct.GmRichText.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rich_returns: Option<GmRichText>,
+
     /// The link to the webpage.
     pub link: Url,
+
+    /// Every keyword in the Manual's search index which points to this function's page.
+    /// This normally includes the function's own name.
+    #[serde(default)]
+    pub keywords: BTreeSet<String>,
+
+    /// Other spellings of this function's name which the Manual documents, such as
+    /// `draw_set_color` for `draw_set_colour`. See [`GmManual::function`] to look up a function by any spelling.
+    ///
+    /// [`GmManual::function`]: struct.GmManual.html#method.function
+    #[serde(default)]
+    pub aliases: BTreeSet<String>,
+
+    /// The images on the function's page, in the order they appear.
+    #[serde(default)]
+    pub images: Vec<GmImage>,
+
+    /// Where in the Manual's source this function was parsed from.
+    #[serde(default)]
+    pub source: Option<GmSource>,
+}
+
+/// A built in constructor scraped from the Gm Manual, such as `new AudioEffect(...)`. This
+/// combines the constructor's signature with the members of the struct it creates.
+#[derive(Debug, Clone, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GmManualConstructor {
+    /// The name of the constructor, without the `new`.
+    pub name: String,
+
+    /// The parameters of the constructor.
+    pub parameters: Vec<GmManualFunctionParameter>,
+
+    /// The count of the number of required parameters.
+    pub required_parameters: usize,
+
+    /// If the final parameter can take "infinite" arguments. See [`GmManualFunction`].
+    ///
+    /// [`GmManualFunction`]: struct.GmManualFunction.html
+    pub is_variadic: bool,
+
+    /// How `required_parameters` and `is_variadic` were worked out.
+    #[serde(default)]
+    pub inference: GmParameterInference,
+
+    /// The members of the struct which the constructor creates.
+    pub members: Vec<GmManualStructMember>,
+
+    /// The example given in the Manual. Some constructors don't have one, so this may be empty.
+    pub example: String,
+
+    /// The example as a [`GmRichText`]. This is only present if the parser was asked for it.
+    ///
+    /// [`GmRichText`]: struct.GmRichText.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rich_example: Option<GmRichText>,
+
+    /// The description of what the constructor makes.
+    pub description: String,
+
+    /// The description as a [`GmRichText`], which can be rendered as something other than
+    /// Markdown. This is only present if the parser was asked for it.
+    ///
+    /// [`GmRichText`]: struct.GmRichText.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rich_description: Option<GmRichText>,
+
+    /// The description in every other language ingested, keyed by language.
+    #[serde(default)]
+    pub descriptions: BTreeMap<Lang, String>,
+
+    /// The link to the webpage.
+    pub link: Url,
+
+    /// Every keyword in the Manual's search index which points to this constructor's page.
+    #[serde(default)]
+    pub keywords: BTreeSet<String>,
+
+    /// The images on the constructor's page, in the order they appear.
+    #[serde(default)]
+    pub images: Vec<GmImage>,
+
+    /// Where in the Manual's source this constructor was parsed from.
+    #[serde(default)]
+    pub source: Option<GmSource>,
+}
+
+/// A member of a struct made by a [`GmManualConstructor`].
+///
+/// [`GmManualConstructor`]: struct.GmManualConstructor.html
+#[derive(Debug, Clone, PartialEq, Eq, Ord, PartialOrd, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GmManualStructMember {
+    /// The name of the member.
+    pub name: String,
+
+    /// The type of the member. This is empty if the Manual doesn't give one.
+    pub data_type: String,
+
+    /// A description given of the member.
+    pub description: String,
+
+    /// The description as a [`GmRichText`]. This is only present if the parser was asked for it.
+    ///
+    /// [`GmRichText`]: struct.GmRichText.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rich_description: Option<GmRichText>,
 }
 
 /// A variable scraped from the GmManual.
@@ -70,14 +335,76 @@ pub struct GmManualVariable {
     /// The example given in the Manual.
     pub example: String,
 
+    /// The example as a [`GmRichText`]. This is only present if the parser was asked for it.
+    ///
+    /// [`GmRichText`]: struct.GmRichText.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rich_example: Option<GmRichText>,
+
     /// The description of what the variable does.
     pub description: String,
 
+    /// The description as a [`GmRichText`], which can be rendered as something other than
+    /// Markdown. This is only present if the parser was asked for it.
+    ///
+    /// [`GmRichText`]: struct.GmRichText.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rich_description: Option<GmRichText>,
+
+    /// The description in every other language ingested, keyed by language.
+    #[serde(default)]
+    pub descriptions: BTreeMap<Lang, String>,
+
     /// The type of the variable.
     pub returns: String,
 
-    /// The link to the webpage.
+    /// The type as a [`GmRichText`]. This is only present if the parser was asked for it.
+    ///
+    /// [`GmRichText`]: struct.GmRichText.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rich_returns: Option<GmRichText>,
+
+    /// The link to the webpage. For a variable from an overview table, this is the overview
+    /// page it was found on.
     pub link: Url,
+
+    /// Where the variable is documented: on a page of its own, or only in a table on an
+    /// overview page.
+    #[serde(default)]
+    pub origin: GmManualVariableOrigin,
+
+    /// Every keyword in the Manual's search index which points to this variable's page.
+    /// Variables can share pages, so this may include the names of other variables.
+    #[serde(default)]
+    pub keywords: BTreeSet<String>,
+
+    /// Other spellings of this variable's name which the Manual documents.
+    #[serde(default)]
+    pub aliases: BTreeSet<String>,
+
+    /// The images used to document the variable, in the order they appear.
+    #[serde(default)]
+    pub images: Vec<GmImage>,
+
+    /// Where in the Manual's source this variable was parsed from. For variables from an
+    /// overview page, this is their row of the table.
+    #[serde(default)]
+    pub source: Option<GmSource>,
+}
+
+/// Where a [`GmManualVariable`] is documented in the Manual.
+///
+/// [`GmManualVariable`]: struct.GmManualVariable.html
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash, Default, Serialize, Deserialize,
+)]
+pub enum GmManualVariableOrigin {
+    /// A page of its own, with a syntax, a return type and an example.
+    #[default]
+    Page,
+    /// Only a row in a table on an overview page, such as the instance variables of an
+    /// object. These have no example.
+    OverviewTable,
 }
 
 /// A parameter and description from the manual. Parameters do not directly indicate if they are optional
@@ -92,6 +419,58 @@ pub struct GmManualFunctionParameter {
 
     /// A description given of the parameter.
     pub description: String,
+
+    /// The description as a [`GmRichText`], if the parser was asked for it.
+    ///
+    /// [`GmRichText`]: struct.GmRichText.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rich_description: Option<GmRichText>,
+}
+
+/// How the parameter properties of a [`GmManualFunction`] or [`GmManualConstructor`] were
+/// inferred. The Manual doesn't mark parameters as optional or variadic in any one consistent
+/// way, so these are guesses from the signature and the argument table, which can disagree.
+///
+/// [`GmManualFunction`]: struct.GmManualFunction.html
+/// [`GmManualConstructor`]: struct.GmManualConstructor.html
+#[derive(Debug, Clone, PartialEq, Eq, Ord, PartialOrd, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GmParameterInference {
+    /// Where the first optional parameter was found, which decides `required_parameters`.
+    /// This is [`Assumed`] if no parameter looked optional, so every parameter is required.
+    ///
+    /// [`Assumed`]: enum.GmInferenceSource.html#variant.Assumed
+    pub required_parameters: GmInferenceSource,
+
+    /// Where the parameters were found to be variadic. This is [`Assumed`] if nothing looked
+    /// variadic, so `is_variadic` is false.
+    ///
+    /// [`Assumed`]: enum.GmInferenceSource.html#variant.Assumed
+    pub is_variadic: GmInferenceSource,
+
+    /// The names of the parameters as written in the signature, such as `[value]` or `...`.
+    pub signature: Vec<String>,
+
+    /// Every place where the signature and the argument table disagreed, in either direction,
+    /// on whether a parameter is optional or variadic, or on the count or names of the
+    /// parameters, in plain English.
+    pub conflicts: Vec<String>,
+}
+
+/// Where an inferred property of a function came from.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash, Default, Serialize, Deserialize,
+)]
+pub enum GmInferenceSource {
+    /// The function's signature, such as square brackets around optional parameters, or `...`.
+    Signature,
+    /// The parameter column of the argument table, such as `[optional]` after a name.
+    Table,
+    /// A keyword in a parameter's description, such as "optional".
+    DescriptionKeyword,
+    /// Nothing said otherwise, so the property has its default.
+    #[default]
+    Assumed,
 }
 
 /// A constant parsed from the GmManual.
@@ -109,10 +488,522 @@ pub struct GmManualConstant {
     /// A description of the constant. This is very rarely an empty string (only "cursor_none").
     pub description: String,
 
+    /// The description as a [`GmRichText`], if the parser was asked for it.
+    ///
+    /// [`GmRichText`]: struct.GmRichText.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rich_description: Option<GmRichText>,
+
+    /// The description in every other language ingested, keyed by language.
+    #[serde(default)]
+    pub descriptions: BTreeMap<Lang, String>,
+
     /// The link to the webpage.
     pub link: Url,
 
     /// Additional descriptors present. Most of the time, this will be None, but can
     /// have some Descriptors and Values present.
     pub secondary_descriptors: Option<BTreeMap<String, String>>,
+
+    /// The secondary descriptors as [`GmRichText`], keyed like `secondary_descriptors`. This
+    /// is only present if the parser was asked for it.
+    ///
+    /// [`GmRichText`]: struct.GmRichText.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rich_secondary_descriptors: Option<BTreeMap<String, GmRichText>>,
+
+    /// Every keyword in the Manual's search index which names this constant, such as
+    /// `GM_build_date`. Most constants are only found in tables, so this is often empty.
+    #[serde(default)]
+    pub keywords: BTreeSet<String>,
+
+    /// Other spellings of this constant's name which the Manual documents, such as `c_gray`
+    /// for `c_grey`.
+    #[serde(default)]
+    pub aliases: BTreeSet<String>,
+
+    /// What sort of constant this is.
+    #[serde(default)]
+    pub kind: GmManualConstantKind,
+
+    /// The images in the constant's row of its table, such as the pictures of the cursors.
+    #[serde(default)]
+    pub images: Vec<GmImage>,
+
+    /// Where in the Manual's source this constant was parsed from, which is its row of the table.
+    #[serde(default)]
+    pub source: Option<GmSource>,
+}
+
+/// The kind of a [`GmManualConstant`].
+///
+/// [`GmManualConstant`]: struct.GmManualConstant.html
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash, Default, Serialize, Deserialize,
+)]
+pub enum GmManualConstantKind {
+    /// A macro which the compiler fills in at build time, such as `GM_build_date` or
+    /// `GM_runtime_version`.
+    Macro,
+    /// A plain constant, such as `c_red`.
+    #[default]
+    Constant,
+    /// A member of a built-in enum, such as `AudioEffectType.Reverb1`. The constant's name
+    /// will be the full dotted name, and the enum will be in [`GmManual::enums`].
+    ///
+    /// [`GmManual::enums`]: struct.GmManual.html#structfield.enums
+    EnumMember,
+}
+
+/// A built-in enum, such as `AudioEffectType`, grouping together the constants which
+/// are its members.
+#[derive(Debug, Clone, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GmManualEnum {
+    /// The name of the enum.
+    pub name: String,
+
+    /// The names of the members of the enum, without the enum's name, in the order the Manual
+    /// lists them. Each member is in [`GmManual::constants`] under its full dotted name, like
+    /// `AudioEffectType.Reverb1`.
+    ///
+    /// [`GmManual::constants`]: struct.GmManual.html#structfield.constants
+    pub members: Vec<String>,
+
+    /// The link to the webpage its first member was found on.
+    pub link: Url,
+}
+
+/// An operator or accessor documented in the GML overview pages of the Manual.
+#[derive(Debug, Clone, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GmManualOperator {
+    /// The symbol of the operator, such as `??=`, `div` or `[|`.
+    pub symbol: String,
+
+    /// What sort of operator this is.
+    pub kind: GmManualOperatorKind,
+
+    /// The precedence of the operator, if the Manual gives one. Lower numbers bind tighter.
+    pub precedence: Option<usize>,
+
+    /// The description of the operator. This is the first paragraph, list item, or table row
+    /// in which the Manual mentions the operator, so it will often describe sibling operators too.
+    pub description: String,
+
+    /// The description as a [`GmRichText`]. This is only present if the parser was asked for it.
+    ///
+    /// [`GmRichText`]: struct.GmRichText.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rich_description: Option<GmRichText>,
+
+    /// The link to the webpage.
+    pub link: Url,
+}
+
+/// The kind of a [`GmManualOperator`].
+///
+/// [`GmManualOperator`]: struct.GmManualOperator.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum GmManualOperatorKind {
+    /// Operators such as `+`, `div`, `mod` and `++`.
+    Arithmetic,
+    /// Operators such as `==`, `<` and `>=`.
+    Comparison,
+    /// Operators such as `&&`, `or` and `!`.
+    Logical,
+    /// Operators such as `&`, `<<` and `~`.
+    Bitwise,
+    /// Operators such as `=`, `+=` and `|=`.
+    Assignment,
+    /// The nullish operators, `??` and `??=`.
+    Nullish,
+    /// The ternary operator, `?`, which is always used with `:`.
+    Ternary,
+    /// The data structure, array and struct accessors, such as `[|`, `[?` and `[$`.
+    Accessor,
+}
+
+/// A GLSL ES built-in which GameMaker provides to shaders, such as `gm_BaseTexture` or `in_Position`.
+#[derive(Debug, Clone, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GmManualShaderBuiltin {
+    /// The name of the built-in.
+    pub name: String,
+
+    /// The GLSL type of the built-in, such as `sampler2D` or `mat4[MATRIX_MAX]`. This is
+    /// taken from the declarations in the Manual's example shaders, and will be an empty
+    /// string if the Manual never declares the built-in.
+    pub glsl_type: String,
+
+    /// What sort of built-in this is.
+    pub kind: GmManualShaderBuiltinKind,
+
+    /// The description of the built-in.
+    pub description: String,
+
+    /// The description as a [`GmRichText`]. This is only present if the parser was asked for it.
+    ///
+    /// [`GmRichText`]: struct.GmRichText.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rich_description: Option<GmRichText>,
+
+    /// The link to the webpage.
+    pub link: Url,
+}
+
+/// The kind of a [`GmManualShaderBuiltin`].
+///
+/// [`GmManualShaderBuiltin`]: struct.GmManualShaderBuiltin.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum GmManualShaderBuiltinKind {
+    /// A uniform set by GameMaker, such as `gm_BaseTexture`.
+    Uniform,
+    /// A vertex attribute, such as `in_Position`.
+    Attribute,
+    /// A constant, such as `MATRIX_WORLD_VIEW_PROJECTION`.
+    Constant,
+}
+
+/// A compiler or runner error message documented in the Manual.
+#[derive(Debug, Clone, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GmManualError {
+    /// The message, as written in the Manual. Parts of the message which change between
+    /// errors are written as placeholders, such as `<name>`, `{0}`, `%s` or `...`.
+    pub pattern: String,
+
+    /// The category the Manual files the error under, such as "Compiler Errors". This is the
+    /// heading above the table the error was found in.
+    pub category: String,
+
+    /// The Manual's explanation of the error.
+    pub explanation: String,
+
+    /// The explanation as a [`GmRichText`]. This is only present if the parser was asked for it.
+    ///
+    /// [`GmRichText`]: struct.GmRichText.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rich_explanation: Option<GmRichText>,
+
+    /// The link to the webpage.
+    pub link: Url,
+}
+
+impl GmManualError {
+    /// Checks if a concrete error message, such as a line from a player's log, matches this
+    /// error's pattern. Placeholders match any text, the comparison ignores case and whitespace
+    /// differences, and the pattern may appear anywhere within the message.
+    pub fn matches(&self, message: &str) -> bool {
+        self.match_specificity(message).is_some()
+    }
+
+    /// Returns how many characters of literal text matched, if the message matches at all.
+    fn match_specificity(&self, message: &str) -> Option<usize> {
+        let message = normalize_error_text(message);
+        let literals = error_pattern_literals(&self.pattern);
+        if literals.is_empty() {
+            return None;
+        }
+
+        let mut remaining = message.as_str();
+        for literal in literals.iter() {
+            let found = remaining.find(literal.as_str())?;
+            remaining = &remaining[found + literal.len()..];
+        }
+
+        Some(literals.iter().map(|v| v.len()).sum())
+    }
+}
+
+/// An image or figure from the Manual.
+#[derive(Debug, Clone, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GmImage {
+    /// The link to the image. This is the image's Url in the online Manual, unless the
+    /// images were exported, in which case it is the path to the exported copy.
+    pub src: String,
+
+    /// The alt text of the image, which is often empty.
+    pub alt: String,
+
+    /// The caption of the figure the image is in, or its title if it isn't in a figure.
+    pub caption: Option<String>,
+}
+
+/// Where an entry came from in the Manual's Html, so a wrong entry can be traced back to
+/// the Html which made it.
+#[derive(Debug, Clone, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GmSource {
+    /// The path of the source file, relative to the root of the Manual, with `/` separators.
+    pub path: String,
+
+    /// The `id` or `name` of the closest element which has one, which can be used to jump to
+    /// the entry in the page. For a page's entry, this is its title's, or that of an element
+    /// around the title. Many entries have no such element.
+    pub anchor: Option<String>,
+
+    /// A hash of the Html fragment the entry was parsed from. This only changes when the
+    /// fragment does, so comparing hashes between two Manuals shows which entries changed.
+    pub hash: String,
+}
+
+/// The British and American spellings which GML accepts interchangeably in the names of
+/// some of its built-ins. Not every built-in has both spellings, so only those the Manual
+/// documents are recorded as aliases.
+pub const SPELLING_VARIANTS: [(&str, &str); 2] = [("colour", "color"), ("grey", "gray")];
+
+/// Every other way a name could be spelled, swapping between the [`SPELLING_VARIANTS`]. These
+/// are only candidates, which GML may not accept. Names without any variant spellings in them
+/// return an empty set.
+///
+/// [`SPELLING_VARIANTS`]: constant.SPELLING_VARIANTS.html
+pub fn alternate_spellings(name: &str) -> BTreeSet<String> {
+    let mut output = BTreeSet::new();
+    output.insert(name.to_string());
+
+    for (british, american) in SPELLING_VARIANTS {
+        let mut respelled = BTreeSet::new();
+        for spelling in output.iter() {
+            respelled.insert(spelling.replace(british, american));
+            respelled.insert(spelling.replace(american, british));
+        }
+        output.append(&mut respelled);
+    }
+
+    output.remove(name);
+    output
+}
+
+impl GmManual {
+    /// Looks up a function by its name or by any of its [`aliases`].
+    ///
+    /// [`aliases`]: struct.GmManualFunction.html#structfield.aliases
+    pub fn function(&self, name: &str) -> Option<&GmManualFunction> {
+        lookup(&self.functions, name, |v| &v.aliases)
+    }
+
+    /// Looks up a variable by its name or by any of its [`aliases`].
+    ///
+    /// [`aliases`]: struct.GmManualVariable.html#structfield.aliases
+    pub fn variable(&self, name: &str) -> Option<&GmManualVariable> {
+        lookup(&self.variables, name, |v| &v.aliases)
+    }
+
+    /// Looks up a constant by its name or by any of its [`aliases`].
+    ///
+    /// [`aliases`]: struct.GmManualConstant.html#structfield.aliases
+    pub fn constant(&self, name: &str) -> Option<&GmManualConstant> {
+        lookup(&self.constants, name, |v| &v.aliases)
+    }
+}
+
+fn lookup<'a, T>(
+    map: &'a BTreeMap<String, T>,
+    name: &str,
+    aliases: impl Fn(&T) -> &BTreeSet<String>,
+) -> Option<&'a T> {
+    map.get(name).or_else(|| {
+        alternate_spellings(name)
+            .iter()
+            .filter_map(|spelling| map.get(spelling))
+            .find(|entry| aliases(entry).contains(name))
+    })
+}
+
+impl GmManual {
+    /// Finds the documented error which matches a concrete error message. If several
+    /// patterns match, the one with the most literal text, and therefore the most specific
+    /// one, is returned.
+    pub fn find_error(&self, message: &str) -> Option<&GmManualError> {
+        self.errors
+            .values()
+            .filter_map(|error| error.match_specificity(message).map(|s| (s, error)))
+            .max_by_key(|(specificity, _)| *specificity)
+            .map(|(_, error)| error)
+    }
+}
+
+fn normalize_error_text(txt: &str) -> String {
+    txt.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Splits an error pattern into the literal pieces between its placeholders.
+fn error_pattern_literals(pattern: &str) -> Vec<String> {
+    let pattern = normalize_error_text(pattern);
+    let mut literals = vec![];
+    let mut current = String::new();
+
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        let closing = match c {
+            '<' => Some('>'),
+            '{' => Some('}'),
+            _ => None,
+        };
+
+        let is_placeholder = if let Some(closing) = closing {
+            if chars.clone().any(|v| v == closing) {
+                for inner in chars.by_ref() {
+                    if inner == closing {
+                        break;
+                    }
+                }
+                true
+            } else {
+                false
+            }
+        } else if c == '%'
+            && chars
+                .peek()
+                .map(|v| v.is_ascii_alphabetic())
+                .unwrap_or_default()
+        {
+            chars.next();
+            true
+        } else if c == '…' {
+            true
+        } else if c == '.' && chars.peek() == Some(&'.') {
+            while chars.peek() == Some(&'.') {
+                chars.next();
+            }
+            true
+        } else {
+            false
+        };
+
+        if is_placeholder {
+            if !current.trim().is_empty() {
+                literals.push(current.trim().to_string());
+            }
+            current.clear();
+        } else {
+            current.push(c);
+        }
+    }
+
+    if !current.trim().is_empty() {
+        literals.push(current.trim().to_string());
+    }
+
+    literals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(pattern: &str) -> GmManualError {
+        GmManualError {
+            pattern: pattern.to_string(),
+            category: String::new(),
+            explanation: String::new(),
+            rich_explanation: None,
+            link: Url::parse("https://manual.yoyogames.com/").unwrap(),
+        }
+    }
+
+    #[test]
+    fn error_patterns() {
+        let not_set = error("Variable <name> not set before reading it.");
+        assert!(not_set.matches(
+            "ERROR in action number 1 of Step Event0 for object obj_player:\n\
+             Variable obj_player.speedx(100003, -2147483648) not set before reading it."
+        ));
+        assert!(!not_set.matches("variable foo not set before writing it."));
+
+        assert!(error("DoAdd :: Execution Error").matches("DoAdd  :: execution error"));
+        assert!(error("Malformed %s statement").matches("Malformed if statement"));
+        assert!(error("Unable to find ... {0}").matches("Unable to find instance 4 for 100"));
+        assert!(!error("<name>").matches("anything at all"));
+    }
+
+    #[test]
+    fn spellings() {
+        assert_eq!(
+            alternate_spellings("draw_set_colour"),
+            BTreeSet::from(["draw_set_color".to_string()])
+        );
+        assert_eq!(
+            alternate_spellings("c_gray"),
+            BTreeSet::from(["c_grey".to_string()])
+        );
+        assert_eq!(alternate_spellings("ds_list_add"), BTreeSet::new());
+        assert_eq!(alternate_spellings("grey_colour").len(), 3);
+    }
+
+    #[test]
+    fn lookup_by_alias() {
+        let mut manual = GmManual::default();
+        manual.constants.insert(
+            "c_grey".to_string(),
+            GmManualConstant {
+                name: "c_grey".to_string(),
+                description: String::new(),
+                rich_description: None,
+                descriptions: BTreeMap::new(),
+                link: Url::parse("https://manual.yoyogames.com/").unwrap(),
+                secondary_descriptors: None,
+                rich_secondary_descriptors: None,
+                keywords: BTreeSet::new(),
+                aliases: alternate_spellings("c_grey"),
+                kind: GmManualConstantKind::Constant,
+                images: vec![],
+                source: None,
+            },
+        );
+
+        assert!(manual.constant("c_grey").is_some());
+        assert_eq!(
+            manual.constant("c_gray").map(|v| v.name.as_str()),
+            Some("c_grey")
+        );
+        assert!(manual.constant("c_green").is_none());
+    }
+
+    #[test]
+    fn schema_version() {
+        let json = serde_json::to_string(&GmManual::default()).unwrap();
+        assert!(serde_json::from_str::<GmManual>(&json).is_ok());
+
+        assert!(json.contains("\"shaderBuiltins\":"));
+
+        let newer = json.replace(
+            &format!("\"schemaVersion\":{}", SCHEMA_VERSION),
+            &format!("\"schemaVersion\":{}", SCHEMA_VERSION + 1),
+        );
+        assert!(serde_json::from_str::<GmManual>(&newer).is_err());
+
+        let older = json.replace(
+            &format!("\"schemaVersion\":{}", SCHEMA_VERSION),
+            "\"schema_version\":11",
+        );
+        let error = serde_json::from_str::<GmManual>(&older).unwrap_err();
+        assert!(error.to_string().contains("schema version 11"), "{}", error);
+    }
+
+    #[test]
+    fn most_specific_error() {
+        let mut manual = GmManual::default();
+        for pattern in ["Execution Error", "DoAdd :: Execution Error"] {
+            manual.errors.insert(pattern.to_string(), error(pattern));
+        }
+
+        assert_eq!(
+            manual
+                .find_error("DoAdd :: Execution Error")
+                .map(|v| v.pattern.as_str()),
+            Some("DoAdd :: Execution Error")
+        );
+        assert_eq!(
+            manual
+                .find_error("DoSub :: Execution Error")
+                .map(|v| v.pattern.as_str()),
+            Some("Execution Error")
+        );
+    }
 }
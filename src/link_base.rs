@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use std::{path::Path, str::FromStr};
+use url::Url;
+
+/// The scheme of links made with [`GmLinkBase::Relative`].
+///
+/// [`GmLinkBase::Relative`]: enum.GmLinkBase.html#variant.Relative
+pub const RELATIVE_LINK_SCHEME: &str = "manual";
+
+/// The base of links made with [`GmLinkBase::Relative`]. Taking it off the front of a link
+/// leaves the link's path relative to the root of the Manual, like
+/// `GameMaker_Language/GML_Reference/Drawing/draw_set_colour.htm`.
+///
+/// [`GmLinkBase::Relative`]: enum.GmLinkBase.html#variant.Relative
+pub const RELATIVE_LINK_BASE: &str = "manual:///";
+
+/// Where links into the Manual point. The online Manual has moved over the years, and an offline
+/// viewer may want links into a local mirror instead.
+#[derive(Debug, Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GmLinkBase {
+    /// `https://manual.yoyogames.com/`, where the Manual used to be.
+    #[default]
+    Legacy,
+    /// `https://manual.gamemaker.io/lts/{lang}/`, the Manual of the long-term support release.
+    Lts,
+    /// `https://manual.gamemaker.io/monthly/{lang}/`, the Manual of the monthly release.
+    Monthly,
+    /// `file://` links into a local mirror of the Manual. Without a folder, links point into
+    /// the folder the Manual was read from.
+    Local(Option<String>),
+    /// Links relative to the root of the Manual, which can be resolved against wherever the
+    /// Manual is hosted. Links are stored under [`RELATIVE_LINK_BASE`], and rendered text
+    /// writes them as relative paths.
+    ///
+    /// [`RELATIVE_LINK_BASE`]: constant.RELATIVE_LINK_BASE.html
+    Relative,
+    /// Any other site, where `{lang}` stands in for the language of the Manual, such as
+    /// `https://example.com/docs/{lang}/`.
+    Custom(String),
+}
+
+impl GmLinkBase {
+    /// The template which links are made from, where `{lang}` stands in for the language of
+    /// the Manual. Local links have no template, since they depend on where the Manual is.
+    pub fn template(&self) -> Option<&str> {
+        match self {
+            GmLinkBase::Legacy => Some("https://manual.yoyogames.com/"),
+            GmLinkBase::Lts => Some("https://manual.gamemaker.io/lts/{lang}/"),
+            GmLinkBase::Monthly => Some("https://manual.gamemaker.io/monthly/{lang}/"),
+            GmLinkBase::Local(_) => None,
+            GmLinkBase::Relative => Some(RELATIVE_LINK_BASE),
+            GmLinkBase::Custom(template) => Some(template),
+        }
+    }
+
+    /// The Url which every page's link is relative to, for a Manual in `lang` which was read
+    /// from `root`. This is `None` if the template isn't a Url, or a local folder can't be
+    /// made into one.
+    pub fn base_url(&self, lang: &str, root: &Path) -> Option<Url> {
+        let mut url = match self {
+            GmLinkBase::Local(folder) => {
+                let folder = folder.as_deref().map(Path::new).unwrap_or(root);
+                Url::from_directory_path(std::path::absolute(folder).ok()?).ok()?
+            }
+            _ => Url::parse(&self.template()?.replace("{lang}", lang)).ok()?,
+        };
+
+        // without a trailing slash, joining a page onto the url would replace its last folder
+        if !url.path().ends_with('/') {
+            url.set_path(&format!("{}/", url.path()));
+        }
+
+        Some(url)
+    }
+}
+
+impl FromStr for GmLinkBase {
+    type Err = String;
+
+    /// Reads a preset, like `lts` or `relative`, a local mirror, like `local` or
+    /// `local=path/to/mirror`, or any other template, like `https://example.com/docs/{lang}/`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "legacy" => Ok(GmLinkBase::Legacy),
+            "lts" => Ok(GmLinkBase::Lts),
+            "monthly" => Ok(GmLinkBase::Monthly),
+            "local" => Ok(GmLinkBase::Local(None)),
+            "relative" => Ok(GmLinkBase::Relative),
+            _ => {
+                if let Some(folder) = s.strip_prefix("local=") {
+                    if folder.trim().is_empty() {
+                        return Err("expected a folder after `local=`".to_string());
+                    }
+                    return Ok(GmLinkBase::Local(Some(folder.to_string())));
+                }
+
+                let custom = GmLinkBase::Custom(s.to_string());
+                match custom.base_url("en", Path::new("")) {
+                    Some(_) => Ok(custom),
+                    None => Err(format!(
+                        "expected `legacy`, `lts`, `monthly`, `local`, `local=PATH`, `relative` \
+                        or a Url, but got `{}`",
+                        s
+                    )),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets() {
+        let base_url = |link_base: &str| {
+            link_base
+                .parse::<GmLinkBase>()
+                .unwrap()
+                .base_url("fr", Path::new("/manual"))
+                .map(|url| url.to_string())
+        };
+
+        assert_eq!(
+            base_url("legacy").as_deref(),
+            Some("https://manual.yoyogames.com/")
+        );
+        assert_eq!(
+            base_url("lts").as_deref(),
+            Some("https://manual.gamemaker.io/lts/fr/")
+        );
+        assert_eq!(
+            base_url("https://example.com/{lang}").as_deref(),
+            Some("https://example.com/fr/")
+        );
+        assert_eq!(
+            base_url("relative")
+                .and_then(|base| Url::parse(&base).ok()?.join("Drawing/a.htm#b").ok())
+                .map(|url| url.to_string()),
+            Some("manual:///Drawing/a.htm#b".to_string())
+        );
+        assert!("manual".parse::<GmLinkBase>().is_err());
+        assert!("local=".parse::<GmLinkBase>().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn local() {
+        let link_base: GmLinkBase = "local".parse().unwrap();
+        let url = link_base.base_url("en", Path::new("/manual")).unwrap();
+
+        assert_eq!(
+            url.join("Drawing/draw_set_colour.htm").unwrap().as_str(),
+            "file:///manual/Drawing/draw_set_colour.htm"
+        );
+    }
+}
@@ -2,38 +2,151 @@
 
 pub use gm_docs_parser::*;
 mod arg;
+mod assets;
+mod context;
+mod encoding;
+mod lang;
 mod markdown;
+mod metadata;
+mod parse_aliases;
 mod parse_constants;
+mod parse_errors;
 mod parse_file;
 mod parse_fnames;
+mod parse_operators;
+mod parse_shaders;
+mod parse_translations;
+mod parse_variables;
+mod source;
+mod verify;
 pub use markdown::Markdown;
 
 use clap::Parser;
+use log::*;
 
 fn main() {
     env_logger::init();
     let arguments: arg::InputOpts = arg::InputOpts::parse();
-    let fnames = parse_fnames::parse_fnames(arguments.input_path);
+    let mut cx =
+        context::ParseContext::new(arguments.input_path.clone(), arguments.parse_options());
+    // the language was checked when the arguments were parsed
+    let headings = lang::Headings::for_lang(&arguments.lang).unwrap();
+    let keywords = parse_fnames::parse_keywords(&cx.base_path);
+    let reference_pages = parse_fnames::reference_pages(&keywords);
+    cx.entry_pages = parse_fnames::entry_pages(&cx.base_path, &reference_pages);
+    let cx = &cx;
 
-    let mut gm_manual = GmManual::default();
-    for fname in fnames {
-        if let Some(success) = parse_file::parse_function_file(&fname) {
+    let mut gm_manual = GmManual {
+        language: arguments.lang.clone(),
+        text_format: arguments.text_format,
+        metadata: metadata::make_metadata(
+            cx,
+            arguments.manual_version.clone(),
+            arguments.ide_version.clone(),
+            arguments.runtime_version.clone(),
+        ),
+        ..Default::default()
+    };
+    for (fname, page_keywords) in reference_pages.iter() {
+        let page_keywords = page_keywords.clone();
+        if let Some(success) = parse_file::parse_function_file(cx, fname, headings) {
             match success {
-                parse_file::DocEntry::Function(v) => {
+                parse_file::DocEntry::Function(mut v) => {
+                    v.keywords = page_keywords;
                     gm_manual.functions.insert(v.name.clone(), v);
                 }
-                parse_file::DocEntry::Variable(v) => {
+                parse_file::DocEntry::Variable(mut v) => {
+                    v.keywords = page_keywords;
                     gm_manual.variables.insert(v.name.clone(), v);
                 }
+                parse_file::DocEntry::Constructor(mut v) => {
+                    v.keywords = page_keywords;
+                    gm_manual.constructors.insert(v.name.clone(), v);
+                }
             }
         }
     }
 
-    let base_path = parse_fnames::base_path();
-    parse_constants::parse_constants(&base_path, &mut gm_manual.constants).unwrap();
+    let base_path = cx.base_path.clone();
+    parse_constants::parse_constants(
+        cx,
+        &base_path,
+        &mut gm_manual.constants,
+        &mut gm_manual.enums,
+        headings,
+    )
+    .unwrap();
+    parse_variables::parse_variable_tables(cx, &base_path, &mut gm_manual.variables, headings)
+        .unwrap();
+    parse_operators::parse_operators(cx, &base_path, &mut gm_manual.operators, headings).unwrap();
+    parse_shaders::parse_shader_builtins(cx, &base_path, &mut gm_manual.shader_builtins, headings)
+        .unwrap();
+    parse_errors::parse_errors(cx, &base_path, &mut gm_manual.errors, headings).unwrap();
+    gm_manual.glossary = cx.take_glossary();
+    parse_aliases::parse_spelling_aliases(cx, &keywords, &mut gm_manual);
+    parse_fnames::constant_keywords(&keywords, &mut gm_manual.constants);
+
+    for (lang, path) in arguments.translation.iter() {
+        parse_translations::parse_translation(cx, lang, path, &reference_pages, &mut gm_manual)
+            .unwrap();
+    }
+
+    if arguments.rich_text == false {
+        markdown::strip_rich_text(&mut gm_manual);
+    }
+
+    let unmatched = parse_fnames::unmatched_keywords(&keywords, &gm_manual);
+    for keyword in unmatched.iter() {
+        warn!("keyword `{}` did not produce an entry", keyword);
+    }
+    info!(
+        "{} of {} keywords did not produce an entry",
+        unmatched.len(),
+        keywords.len()
+    );
+
+    let mut mismatched = 0;
+    let inferences = gm_manual
+        .functions
+        .values()
+        .map(|v| (&v.name, &v.inference))
+        .chain(
+            gm_manual
+                .constructors
+                .values()
+                .map(|v| (&v.name, &v.inference)),
+        );
+    for (name, inference) in inferences {
+        if inference.conflicts.is_empty() == false {
+            mismatched += 1;
+        }
+        for conflict in inference.conflicts.iter() {
+            warn!("`{}`: {}", name, conflict);
+        }
+    }
+    info!(
+        "{} functions have a signature which doesn't match their argument table",
+        mismatched
+    );
+
+    let diagnostics = cx.diagnostics.borrow();
+    for (path, encoding) in diagnostics.transcoded.iter() {
+        warn!("{:?} was read as {}", path, encoding);
+    }
+    info!(
+        "{} pages weren't UTF-8 and were transcoded",
+        diagnostics.transcoded.len()
+    );
+
+    if arguments.verify_markdown {
+        info!(
+            "{} markdown strings don't match their html",
+            diagnostics.verify_failures
+        );
+    }
 
     if arguments.do_not_emit == false {
         let st = serde_json::to_string_pretty(&gm_manual).unwrap();
-        println!("{}", st.replace('\u{a0}', " "));
+        println!("{}", st);
     }
 }
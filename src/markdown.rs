@@ -1,7 +1,10 @@
+use crate::{assets::normalize_path, context::ParseContext};
 use ego_tree::NodeRef;
+use gm_docs_parser::{GmBlock, GmImage, GmInline, GmManual, GmRichText};
 use log::error;
-use scraper::{node::Element, Node};
-use std::{fmt, path::Path, path::PathBuf};
+use scraper::{node::Element, ElementRef, Node};
+use std::path::{Path, PathBuf};
+use url::Url;
 
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Markdown {
@@ -9,45 +12,293 @@ pub struct Markdown {
     style: Style,
 }
 
-impl fmt::Display for Markdown {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let txt = &self.txt;
-        match &self.style {
-            Style::Hyperlink(dest) => write!(
-                f,
-                " [{}]({})",
-                txt,
-                super::parse_fnames::convert_to_url(dest)
-            ),
-            Style::Tooltip(dest) => write!(f, "{} ({})", txt, dest),
-            Style::Plain => write!(f, "{}", txt),
-            Style::Bold => write!(f, "**{}**", txt),
-            Style::Italic => write!(f, "*{}*", txt),
-            Style::CodeSnippet => write!(f, "`{}`", txt),
-            Style::CodeFull => write!(f, "```\n{}\n```", txt),
-        }
-    }
-}
-
 impl Markdown {
     fn new(txt: String, style: Style) -> Markdown {
         Markdown { txt, style }
     }
 
-    pub fn convert_to_markdown(directory: &Path, container: &NodeRef<Node>) -> String {
-        let mut st = Vec::new();
-        Self::flatten_container(container, directory, &mut st);
+    /// Converts Html to text in the format we're writing, which is Markdown unless we were
+    /// asked for something else.
+    pub fn convert_to_text(
+        cx: &ParseContext,
+        directory: &Path,
+        container: &NodeRef<Node>,
+    ) -> String {
+        cx.render(&Self::convert_to_rich_text(cx, directory, container))
+    }
 
-        let st = Self::simplify_markdown(st);
-        let mut output = String::new();
+    pub fn convert_to_rich_text(
+        cx: &ParseContext,
+        directory: &Path,
+        container: &NodeRef<Node>,
+    ) -> GmRichText {
+        let output = Self::make_rich_text(cx, directory, container);
+        crate::verify::check(cx, container, &output.to_markdown());
 
-        for md in st {
-            output.push_str(&md.to_string());
+        output
+    }
+
+    /// Converts Html to text with no formatting at all, for reading names and headings.
+    pub fn convert_to_plain_text(
+        cx: &ParseContext,
+        directory: &Path,
+        container: &NodeRef<Node>,
+    ) -> String {
+        Self::make_rich_text(cx, directory, container).to_plain_text()
+    }
+
+    /// Converts a run of sibling nodes, such as the text and code following a heading, as if
+    /// they were all in one container. Loose text between them flows into paragraphs.
+    pub fn convert_siblings_to_rich_text<'a>(
+        cx: &ParseContext,
+        directory: &Path,
+        nodes: impl IntoIterator<Item = NodeRef<'a, Node>>,
+    ) -> GmRichText {
+        let mut output = GmRichText::default();
+        let mut inline = Vec::new();
+
+        for node in nodes {
+            Self::push_node(cx, &node, directory, &mut output, &mut inline);
         }
+        Self::end_paragraph(cx, &mut output, &mut inline, directory);
+        cx.options.normalization.normalize_rich_text(&mut output);
+
+        output
+    }
+
+    fn make_rich_text(
+        cx: &ParseContext,
+        directory: &Path,
+        container: &NodeRef<Node>,
+    ) -> GmRichText {
+        let mut output = GmRichText::default();
+        let mut inline = Vec::new();
+
+        Self::push_node(cx, container, directory, &mut output, &mut inline);
+        Self::end_paragraph(cx, &mut output, &mut inline, directory);
+        cx.options.normalization.normalize_rich_text(&mut output);
 
         output
     }
 
+    /// Adds a node to the text, whether it's a block, inline, or an inline container, like a
+    /// `td`, holding blocks.
+    fn push_node(
+        cx: &ParseContext,
+        node: &NodeRef<Node>,
+        directory: &Path,
+        output: &mut GmRichText,
+        inline: &mut Vec<Markdown>,
+    ) {
+        let has_blocks = node.descendants().any(|node| is_block(&node));
+        if has_blocks && is_block(node) == false {
+            for child in node.children() {
+                Self::push_block(cx, &child, directory, output, inline);
+            }
+        } else {
+            Self::push_block(cx, node, directory, output, inline);
+        }
+    }
+
+    /// Adds a node to the text. Inline nodes are added to the current paragraph, while blocks
+    /// end the paragraph and are added after it.
+    fn push_block(
+        cx: &ParseContext,
+        node: &NodeRef<Node>,
+        directory: &Path,
+        output: &mut GmRichText,
+        inline: &mut Vec<Markdown>,
+    ) {
+        let element = match node.value().as_element() {
+            Some(element) if is_block(node) => element,
+            _ => {
+                Self::flatten_container(cx, node, directory, inline);
+                return;
+            }
+        };
+        Self::end_paragraph(cx, output, inline, directory);
+
+        let is_note = element.classes().any(|c| c == "note" || c == "warning");
+        let heading_level = match element.name() {
+            "h1" => Some(1),
+            "h2" => Some(2),
+            "h3" => Some(3),
+            "h4" => Some(4),
+            "h5" => Some(5),
+            "h6" => Some(6),
+            _ => None,
+        };
+
+        if let Some(level) = heading_level {
+            let mut runs = Vec::new();
+            for child in node.children() {
+                Self::flatten_container(cx, &child, directory, &mut runs);
+            }
+
+            output.blocks.push(GmBlock::Heading {
+                level,
+                content: Self::into_inlines(cx, runs),
+            });
+        } else if element.name() == "ul" || element.name() == "ol" {
+            let items = node
+                .children()
+                .filter(|child| {
+                    child
+                        .value()
+                        .as_element()
+                        .map(|e| e.name() == "li")
+                        .unwrap_or_default()
+                })
+                .map(|li| Self::make_rich_text(cx, directory, &li))
+                .collect();
+
+            output.blocks.push(GmBlock::List {
+                ordered: element.name() == "ol",
+                items,
+            });
+        } else if element.name() == "table" {
+            output.blocks.push(Self::make_table(cx, node, directory));
+        } else if is_note {
+            let mut content = GmRichText::default();
+            let mut note_inline = Vec::new();
+            for child in node.children() {
+                Self::push_block(cx, &child, directory, &mut content, &mut note_inline);
+            }
+            Self::end_paragraph(cx, &mut content, &mut note_inline, directory);
+
+            output.blocks.push(GmBlock::Note { content });
+        } else if element.attr("class") == Some("code") {
+            let code = code_text(node);
+            if code.is_empty() == false {
+                output.blocks.push(GmBlock::CodeBlock {
+                    language: Some(code_language(directory, &code).to_string()),
+                    code,
+                });
+            }
+        } else {
+            for child in node.children() {
+                Self::push_block(cx, &child, directory, output, inline);
+            }
+        }
+
+        Self::end_paragraph(cx, output, inline, directory);
+    }
+
+    /// Tables become a header, if the first row is all `th`, and rows of inline cells.
+    fn make_table(cx: &ParseContext, table: &NodeRef<Node>, directory: &Path) -> GmBlock {
+        let is_element = |node: &NodeRef<Node>, names: &[&str]| {
+            node.value()
+                .as_element()
+                .map(|e| names.contains(&e.name()))
+                .unwrap_or_default()
+        };
+
+        // rows are either right in the table, or in its sections, but never in nested tables
+        let rows = table.children().flat_map(|child| {
+            let rows: Vec<_> = if is_element(&child, &["thead", "tbody", "tfoot"]) {
+                child.children().collect()
+            } else {
+                vec![child]
+            };
+            rows.into_iter().filter(|row| is_element(row, &["tr"]))
+        });
+
+        let mut header = vec![];
+        let mut body = vec![];
+        for (i, row) in rows.enumerate() {
+            let cells: Vec<_> = row
+                .children()
+                .filter(|cell| is_element(cell, &["th", "td"]))
+                .collect();
+            let is_header = i == 0 && cells.iter().all(|cell| is_element(cell, &["th"]));
+
+            let cells: Vec<_> = cells
+                .into_iter()
+                .map(|cell| {
+                    let mut runs = Vec::new();
+                    for child in cell.children() {
+                        Self::flatten_container(cx, &child, directory, &mut runs);
+                    }
+                    Self::into_inlines(cx, runs)
+                })
+                .collect();
+
+            if is_header {
+                header = cells;
+            } else {
+                body.push(cells);
+            }
+        }
+
+        GmBlock::Table { header, rows: body }
+    }
+
+    /// Moves the inline runs so far into the text. Full code runs become code blocks, and
+    /// everything else becomes paragraphs, unless it's only whitespace.
+    fn end_paragraph(
+        cx: &ParseContext,
+        output: &mut GmRichText,
+        inline: &mut Vec<Markdown>,
+        directory: &Path,
+    ) {
+        let mut paragraph = Vec::new();
+
+        for md in Self::simplify_markdown(std::mem::take(inline)) {
+            if md.style == Style::CodeFull {
+                push_paragraph(cx, output, std::mem::take(&mut paragraph));
+
+                let code = clean_code(&md.txt, false);
+                if code.is_empty() == false {
+                    output.blocks.push(GmBlock::CodeBlock {
+                        language: Some(code_language(directory, &code).to_string()),
+                        code,
+                    });
+                }
+            } else {
+                paragraph.push(md);
+            }
+        }
+        push_paragraph(cx, output, paragraph);
+
+        fn push_paragraph(cx: &ParseContext, output: &mut GmRichText, paragraph: Vec<Markdown>) {
+            let mut content = Markdown::into_inlines(cx, paragraph);
+            if let Some(GmInline::Plain { text }) = content.first_mut() {
+                *text = text.trim_start().to_string();
+            }
+            if let Some(GmInline::Plain { text }) = content.last_mut() {
+                *text = text.trim_end().to_string();
+            }
+            content.retain(|inline| {
+                matches!(inline, GmInline::Plain { text } if text.is_empty()) == false
+            });
+
+            if content.is_empty() == false {
+                output.blocks.push(GmBlock::Paragraph { content });
+            }
+        }
+    }
+
+    fn into_inlines(cx: &ParseContext, runs: Vec<Markdown>) -> Vec<GmInline> {
+        Self::simplify_markdown(runs)
+            .into_iter()
+            .map(|md| md.into_inline(cx))
+            .collect()
+    }
+
+    fn into_inline(self, cx: &ParseContext) -> GmInline {
+        let text = self.txt;
+        match self.style {
+            Style::Hyperlink(link) => link.into_inline(cx, text),
+            // the definition lives in the glossary, so we just write the term
+            Style::Tooltip(term) => GmInline::GlossaryTerm { text, term },
+            Style::Plain | Style::CodeFull => GmInline::Plain { text },
+            Style::Image(src) => GmInline::Image { alt: text, src },
+            Style::Bold => GmInline::Bold { text },
+            Style::Italic => GmInline::Italic { text },
+            Style::CodeSnippet => GmInline::Code { text },
+        }
+    }
+
     fn simplify_markdown(input: Vec<Markdown>) -> Vec<Markdown> {
         if input.is_empty() {
             return input;
@@ -84,38 +335,44 @@ impl Markdown {
         output
     }
 
-    fn flatten_container(container: &NodeRef<Node>, directory: &Path, output: &mut Vec<Markdown>) {
+    fn flatten_container(
+        cx: &ParseContext,
+        container: &NodeRef<Node>,
+        directory: &Path,
+        output: &mut Vec<Markdown>,
+    ) {
         if let Some(txt) = container.value().as_text() {
             output.push(Markdown::new(txt.to_string(), Style::Plain));
             return;
         }
 
         let this_container = container.value().as_element().unwrap();
+        if this_container.name() == "a" && this_container.classes().any(|c| c == "tooltip") {
+            if let Some(tooltip) = Self::make_tooltip(cx, container) {
+                output.push(tooltip);
+                return;
+            }
+        }
+
+        if this_container.name() == "img" {
+            match Self::make_image(cx, container, directory) {
+                Some(image) => output.push(image),
+                None => error!("We had an <img> with no src!"),
+            }
+            return;
+        }
+
         let style = match this_container.name() {
             "i" | "em" => Style::Italic,
             "b" | "strong" | "h4" => Style::Bold,
             "a" => {
                 if let Some(val) = this_container.attr("href") {
-                    Style::Hyperlink(directory.join(val))
-                } else if let Some(val) = this_container.attr("class") {
-                    if val == "tooltip" {
-                        Style::Tooltip(val.to_string())
-                    } else {
-                        Style::Plain
-                    }
+                    Style::Hyperlink(Link::classify(directory, val))
                 } else {
                     // like what is going on here...
                     Style::Plain
                 }
             }
-            "img" => {
-                if let Some(val) = this_container.attr("src") {
-                    Style::Hyperlink(directory.join(val))
-                } else {
-                    error!("We had an <img> with no src!");
-                    Style::Plain
-                }
-            }
             "p" => {
                 if this_container.attr("class") == Some("code") {
                     Style::CodeFull
@@ -124,7 +381,9 @@ impl Markdown {
                 }
             }
             "tt" => Style::CodeSnippet,
-            "td" | "br" | "span" | "font" => Style::Plain,
+            "td" | "br" | "span" | "font" | "figure" | "figcaption" | "div" | "ul" | "ol"
+            | "li" | "blockquote" | "h1" | "h2" | "h3" | "h5" | "h6" | "table" | "thead"
+            | "tbody" | "tfoot" | "tr" | "th" => Style::Plain,
             o => {
                 error!("Unknown tag encountered {}", o);
                 Style::Plain
@@ -141,7 +400,7 @@ impl Markdown {
                 }
                 Node::Element(_) => {
                     let mut buff = Vec::new();
-                    Self::flatten_container(&child, directory, &mut buff);
+                    Self::flatten_container(cx, &child, directory, &mut buff);
                     output.append(&mut buff);
                     wrote = true;
                 }
@@ -156,24 +415,271 @@ impl Markdown {
         }
     }
 
-    fn flat_make_md(txt_desc: Style, e: &Element) -> Option<Markdown> {
-        if let Style::Hyperlink(dest) = txt_desc {
-            e.attr("alt").map(|txt| {
-                Markdown::new(
-                    format!("[{}]({})", txt, super::parse_fnames::convert_to_url(&dest)),
-                    Style::Plain,
-                )
+    /// Tooltips hold their definition in either their `title` or in a nested tooltip `span`.
+    /// We record the term in the glossary, and mark the term in the text with its key.
+    fn make_tooltip(cx: &ParseContext, container: &NodeRef<Node>) -> Option<Markdown> {
+        let element = ElementRef::wrap(*container)?;
+        let is_definition = |node: &NodeRef<Node>| {
+            node.value()
+                .as_element()
+                .map(|e| e.classes().any(|c| c.starts_with("tooltip")))
+                .unwrap_or_default()
+        };
+
+        let definition = element
+            .value()
+            .attr("title")
+            .or_else(|| element.value().attr("data-tooltip"))
+            .map(|v| v.to_string())
+            .or_else(|| {
+                element
+                    .descendants()
+                    .skip(1)
+                    .find(is_definition)
+                    .and_then(ElementRef::wrap)
+                    .map(|e| e.text().collect())
+            })?;
+
+        let mut term = String::new();
+        for node in element.descendants() {
+            if let Some(txt) = node.value().as_text() {
+                let in_definition = node
+                    .ancestors()
+                    .take_while(|a| a.id() != element.id())
+                    .any(|a| is_definition(&a));
+
+                if in_definition == false {
+                    term.push_str(txt);
+                }
+            }
+        }
+
+        let term = cx.normalize(&term);
+        let definition = cx.normalize(&definition);
+        if term.is_empty() || definition.is_empty() {
+            return None;
+        }
+
+        cx.add_glossary_term(term.clone(), definition);
+
+        Some(Markdown::new(term.clone(), Style::Tooltip(term)))
+    }
+
+    /// Images are recorded for their entry, and then written inline. If they're in a `<figure>`,
+    /// its `<figcaption>` is the caption; otherwise, we fall back to the `title`.
+    fn make_image(
+        cx: &ParseContext,
+        container: &NodeRef<Node>,
+        directory: &Path,
+    ) -> Option<Markdown> {
+        let element = container.value().as_element()?;
+        let src = crate::assets::image_src(cx, directory, element.attr("src")?);
+        let alt = cx.normalize(element.attr("alt").unwrap_or_default());
+
+        let caption = container
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .find(|e| e.value().name() == "figure")
+            .and_then(|figure| {
+                figure
+                    .descendants()
+                    .filter_map(ElementRef::wrap)
+                    .find(|e| e.value().name() == "figcaption")
             })
+            .map(|figcaption| cx.normalize(&figcaption.text().collect::<String>()))
+            .or_else(|| element.attr("title").map(|title| cx.normalize(title)))
+            .filter(|caption| caption.is_empty() == false);
+
+        cx.add_image(GmImage {
+            src: src.clone(),
+            alt: alt.clone(),
+            caption,
+        });
+
+        Some(Markdown::new(alt, Style::Image(src)))
+    }
+
+    fn flat_make_md(txt_desc: Style, e: &Element) -> Option<Markdown> {
+        if let Style::Hyperlink(_) = txt_desc {
+            e.attr("alt")
+                .map(|txt| Markdown::new(txt.to_string(), txt_desc))
         } else {
             None
         }
     }
 }
 
+/// The text of a code example. The manual lays its code out with `<br>` and `&nbsp;`, so if
+/// there are any breaks we read the rest of the whitespace the way a browser would.
+fn code_text(node: &NodeRef<Node>) -> String {
+    let is_break = |node: &NodeRef<Node>| {
+        node.value()
+            .as_element()
+            .map(|e| e.name() == "br")
+            .unwrap_or_default()
+    };
+    let has_breaks = node.descendants().any(|node| is_break(&node));
+
+    let mut code = String::new();
+    for node in node.descendants() {
+        if let Some(txt) = node.value().as_text() {
+            code.push_str(txt);
+        } else if is_break(&node) {
+            code.push('\u{2028}');
+        }
+    }
+
+    clean_code(&code, has_breaks)
+}
+
+/// Tidies up code so it can be pasted as it is. If `collapse` is set, runs of ordinary
+/// whitespace are collapsed like Html, and lines are only broken by `U+2028`, which is how
+/// [`code_text`] writes breaks. Non-breaking spaces are kept as indentation either way.
+fn clean_code(code: &str, collapse: bool) -> String {
+    let code = code.replace("\r\n", "\n").replace('\r', "\n");
+    let code = if collapse {
+        let mut collapsed = String::with_capacity(code.len());
+        for c in code.chars() {
+            if c.is_ascii_whitespace() {
+                if collapsed.ends_with(' ') == false {
+                    collapsed.push(' ');
+                }
+            } else {
+                collapsed.push(c);
+            }
+        }
+
+        collapsed
+            .split('\u{2028}')
+            .map(|line| line.trim_start_matches(' '))
+            .collect::<Vec<_>>()
+            .join("\n")
+    } else {
+        code.replace('\u{2028}', "\n")
+    };
+    let code = code.replace('\u{a0}', " ").replace('\t', "    ");
+
+    let mut lines: Vec<&str> = code.lines().map(|line| line.trim_end()).collect();
+    while lines
+        .first()
+        .map(|line| line.is_empty())
+        .unwrap_or_default()
+    {
+        lines.remove(0);
+    }
+    while lines.last().map(|line| line.is_empty()).unwrap_or_default() {
+        lines.pop();
+    }
+
+    let indent = lines
+        .iter()
+        .filter(|line| line.is_empty() == false)
+        .map(|line| line.len() - line.trim_start_matches(' ').len())
+        .min()
+        .unwrap_or_default();
+
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Code is GML, unless it's a shader on one of the shader pages.
+fn code_language(directory: &Path, code: &str) -> &'static str {
+    let on_shader_page = directory
+        .to_string_lossy()
+        .to_lowercase()
+        .contains("shader");
+    let is_glsl = [
+        "gl_",
+        "void main",
+        "varying ",
+        "uniform ",
+        "attribute ",
+        "precision ",
+    ]
+    .iter()
+    .any(|marker| code.contains(marker));
+
+    if on_shader_page && is_glsl {
+        "glsl"
+    } else {
+        "gml"
+    }
+}
+
+/// The tags which are laid out as blocks, rather than inline with their surrounding text.
+fn is_block(node: &NodeRef<Node>) -> bool {
+    node.value()
+        .as_element()
+        .map(|e| {
+            matches!(
+                e.name(),
+                "p" | "div"
+                    | "ul"
+                    | "ol"
+                    | "li"
+                    | "blockquote"
+                    | "table"
+                    | "h1"
+                    | "h2"
+                    | "h3"
+                    | "h4"
+                    | "h5"
+                    | "h6"
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// Removes every rich text field, for when they weren't asked for.
+pub fn strip_rich_text(gm_manual: &mut GmManual) {
+    for function in gm_manual.functions.values_mut() {
+        function.rich_description = None;
+        function.rich_example = None;
+        function.rich_returns = None;
+        for parameter in function.parameters.iter_mut() {
+            parameter.rich_description = None;
+        }
+    }
+    for constructor in gm_manual.constructors.values_mut() {
+        constructor.rich_description = None;
+        constructor.rich_example = None;
+        for parameter in constructor.parameters.iter_mut() {
+            parameter.rich_description = None;
+        }
+        for member in constructor.members.iter_mut() {
+            member.rich_description = None;
+        }
+    }
+    for variable in gm_manual.variables.values_mut() {
+        variable.rich_description = None;
+        variable.rich_example = None;
+        variable.rich_returns = None;
+    }
+    for constant in gm_manual.constants.values_mut() {
+        constant.rich_description = None;
+        constant.rich_secondary_descriptors = None;
+    }
+    for operator in gm_manual.operators.values_mut() {
+        operator.rich_description = None;
+    }
+    for builtin in gm_manual.shader_builtins.values_mut() {
+        builtin.rich_description = None;
+    }
+    for error in gm_manual.errors.values_mut() {
+        error.rich_explanation = None;
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Ord, PartialOrd)]
 enum Style {
-    Hyperlink(PathBuf),
+    Hyperlink(Link),
+    /// A glossary term, holding its key in the glossary.
     Tooltip(String),
+    /// An image, holding its link.
+    Image(String),
     Plain,
     Bold,
     Italic,
@@ -181,10 +687,93 @@ enum Style {
     CodeFull,
 }
 
+/// Where a hyperlink goes.
+#[derive(Debug, Clone, PartialEq, Eq, Ord, PartialOrd)]
+enum Link {
+    /// Another page of the manual, and the anchor on it, if there is one.
+    Page(PathBuf, Option<String>),
+    /// An anchor on the same page.
+    Anchor(String),
+    /// Anything outside of the manual, such as a website or an email address.
+    External(Url),
+    /// A file in the manual which isn't a page, such as a download.
+    Asset(PathBuf),
+}
+
+impl Link {
+    fn classify(directory: &Path, href: &str) -> Link {
+        let href = href.trim();
+        if let Some(anchor) = href.strip_prefix('#') {
+            return Link::Anchor(anchor.to_string());
+        }
+
+        // relative links fail to parse without a base, so this is only absolute links
+        if let Ok(url) = Url::parse(href) {
+            return Link::External(url);
+        }
+
+        let (path, fragment) = match href.split_once('#') {
+            Some((path, fragment)) => (path, Some(fragment.to_string())),
+            None => (href, None),
+        };
+        let path = normalize_path(&directory.join(path));
+
+        let is_page = path
+            .extension()
+            .map(|e| e == "htm" || e == "html")
+            .unwrap_or_default();
+        if is_page {
+            Link::Page(path, fragment.filter(|f| f.is_empty() == false))
+        } else {
+            Link::Asset(path)
+        }
+    }
+
+    /// Links to pages which document a builtin refer to the builtin itself, so consumers can
+    /// route them however they like.
+    fn into_inline(self, cx: &ParseContext, text: String) -> GmInline {
+        let path = match &self {
+            Link::Anchor(anchor) => {
+                return GmInline::AnchorLink {
+                    text,
+                    anchor: anchor.clone(),
+                }
+            }
+            Link::External(url) => {
+                return GmInline::UrlLink {
+                    text,
+                    url: url.clone(),
+                }
+            }
+            Link::Page(path, _) | Link::Asset(path) => path,
+        };
+
+        let mut url = match cx.try_convert_to_url(path) {
+            Some(url) => url,
+            None => {
+                error!("link to {:?} is outside of the manual", path);
+                return GmInline::Plain { text };
+            }
+        };
+
+        match self {
+            Link::Page(path, fragment) => {
+                url.set_fragment(fragment.as_deref());
+
+                match cx.entry_for_page(&path) {
+                    Some(entry) => GmInline::EntryLink { text, entry, url },
+                    None => GmInline::UrlLink { text, url },
+                }
+            }
+            _ => GmInline::UrlLink { text, url },
+        }
+    }
+}
+
 impl Style {
     pub fn is_combinatorial(&self) -> bool {
         match self {
-            Style::Hyperlink(_) | Style::Tooltip(_) => false,
+            Style::Hyperlink(_) | Style::Tooltip(_) | Style::Image(_) => false,
             Style::Plain | Style::Bold | Style::Italic | Style::CodeSnippet | Style::CodeFull => {
                 true
             }
@@ -195,6 +784,112 @@ impl Style {
 #[cfg(test)]
 mod test {
     use super::*;
+    use scraper::{Html, Selector};
+
+    #[test]
+    fn tooltips() {
+        let cx = ParseContext::new(PathBuf::new(), Default::default());
+        let html = Html::parse_fragment(
+            r#"<p>An <a class="tooltip" title="A unique
+            value">instance ID</a> or <a class="tooltip">struct<span class="tooltiptext">A
+            collection of variables</span></a>.</p>"#,
+        );
+        let p = html.select(&Selector::parse("p").unwrap()).next().unwrap();
+
+        assert_eq!(
+            Markdown::convert_to_text(&cx, Path::new(""), &p),
+            "An [instance ID](glossary:instance%20ID) or [struct](glossary:struct)."
+        );
+        assert_eq!(
+            Markdown::convert_to_rich_text(&cx, Path::new(""), &p).to_html(),
+            "<p>An <dfn data-term=\"instance ID\">instance ID</dfn> or \
+            <dfn data-term=\"struct\">struct</dfn>.</p>"
+        );
+        assert_eq!(
+            Markdown::convert_to_plain_text(&cx, Path::new(""), &p),
+            "An instance ID or struct."
+        );
+
+        let glossary = cx.take_glossary();
+        assert_eq!(glossary["instance ID"], "A unique value");
+        assert_eq!(glossary["struct"], "A collection of variables");
+    }
+
+    #[test]
+    fn code_blocks() {
+        let cx = ParseContext::new(PathBuf::new(), Default::default());
+        let html = Html::parse_fragment(
+            "<p class=\"code\"><br>\r\nif (a)<br>\r\n{<br>\r\n&nbsp;&nbsp;&nbsp;&nbsp;<a href=\"x.htm\">show</a>(1);&nbsp; <br>\r\n}<br></p>",
+        );
+        let p = html.select(&Selector::parse("p").unwrap()).next().unwrap();
+
+        assert_eq!(
+            Markdown::convert_to_text(&cx, Path::new(""), &p),
+            "```gml\nif (a)\n{\n    show(1);\n}\n```"
+        );
+        assert_eq!(
+            code_language(Path::new("Drawing/Shaders"), "void main() {}"),
+            "glsl"
+        );
+        assert_eq!(clean_code("\n\t  a\r\n\t    b  \n\n", false), "a\n  b");
+    }
+
+    #[test]
+    fn blocks() {
+        let cx = ParseContext::new(PathBuf::new(), Default::default());
+        let html = Html::parse_fragment(
+            r#"<div><h3>Options</h3><p>Pick
+            one:</p><ul><li>first <b>bold</b></li><li>second<ol><li>nested</li></ol></li></ul><p class="note">Careful!</p></div>"#,
+        );
+        let div = html
+            .select(&Selector::parse("div").unwrap())
+            .next()
+            .unwrap();
+
+        assert_eq!(
+            Markdown::convert_to_text(&cx, Path::new(""), &div),
+            "### Options\n\nPick one:\n\n- first **bold**\n- second\n\n  1. nested\n\n> Careful!"
+        );
+    }
+
+    #[test]
+    fn tables() {
+        let cx = ParseContext::new(PathBuf::new(), Default::default());
+        let html = Html::parse_fragment(
+            r#"<table><tr><td>Pick one of:<table><tbody><tr><th>Value</th><th>Meaning</th></tr>
+            <tr><td><tt>a | b</tt></td><td>Either <b>a</b> or
+            b</td></tr></tbody></table></td></tr></table>"#,
+        );
+        let td = html.select(&Selector::parse("td").unwrap()).next().unwrap();
+
+        assert_eq!(
+            Markdown::convert_to_text(&cx, Path::new(""), &td),
+            "Pick one of:\n\n| Value | Meaning |\n| --- | --- |\n| `a \\| b` | Either **a** or b |"
+        );
+    }
+
+    #[test]
+    fn links() {
+        let dir = Path::new("man/GameMaker_Language/GML_Reference/Drawing");
+
+        assert_eq!(
+            Link::classify(dir, "../Data_Structures/ds_list_add.htm#arguments"),
+            Link::Page(
+                Path::new("man/GameMaker_Language/GML_Reference/Data_Structures/ds_list_add.htm")
+                    .to_owned(),
+                Some("arguments".to_string())
+            )
+        );
+        assert_eq!(Link::classify(dir, "#top"), Link::Anchor("top".to_string()));
+        assert_eq!(
+            Link::classify(dir, "mailto:help@yoyogames.com"),
+            Link::External(Url::parse("mailto:help@yoyogames.com").unwrap())
+        );
+        assert_eq!(
+            Link::classify(dir, "../../../assets/sample.zip"),
+            Link::Asset(Path::new("man/assets/sample.zip").to_owned())
+        );
+    }
 
     #[test]
     fn test_whatever() {
@@ -243,21 +938,21 @@ mod test {
             vec![
                 Markdown::new(
                     "a".to_string(),
-                    Style::Hyperlink(Path::new("hey").to_owned()),
+                    Style::Hyperlink(Link::Page(Path::new("hey").to_owned(), None)),
                 ),
                 Markdown::new(
                     "a".to_string(),
-                    Style::Hyperlink(Path::new("hey").to_owned()),
+                    Style::Hyperlink(Link::Page(Path::new("hey").to_owned(), None)),
                 ),
             ],
             vec![
                 Markdown::new(
                     "a".to_string(),
-                    Style::Hyperlink(Path::new("hey").to_owned()),
+                    Style::Hyperlink(Link::Page(Path::new("hey").to_owned(), None)),
                 ),
                 Markdown::new(
                     "a".to_string(),
-                    Style::Hyperlink(Path::new("hey").to_owned()),
+                    Style::Hyperlink(Link::Page(Path::new("hey").to_owned(), None)),
                 ),
             ],
         );
@@ -266,23 +961,23 @@ mod test {
             vec![
                 Markdown::new(
                     "a".to_string(),
-                    Style::Hyperlink(Path::new("hey").to_owned()),
+                    Style::Hyperlink(Link::Page(Path::new("hey").to_owned(), None)),
                 ),
                 Markdown::new("b".to_string(), Style::Italic),
                 Markdown::new(
                     "a".to_string(),
-                    Style::Hyperlink(Path::new("hey").to_owned()),
+                    Style::Hyperlink(Link::Page(Path::new("hey").to_owned(), None)),
                 ),
             ],
             vec![
                 Markdown::new(
                     "a".to_string(),
-                    Style::Hyperlink(Path::new("hey").to_owned()),
+                    Style::Hyperlink(Link::Page(Path::new("hey").to_owned(), None)),
                 ),
                 Markdown::new("b".to_string(), Style::Italic),
                 Markdown::new(
                     "a".to_string(),
-                    Style::Hyperlink(Path::new("hey").to_owned()),
+                    Style::Hyperlink(Link::Page(Path::new("hey").to_owned(), None)),
                 ),
             ],
         );
@@ -291,11 +986,11 @@ mod test {
             vec![
                 Markdown::new(
                     "a".to_string(),
-                    Style::Hyperlink(Path::new("hey").to_owned()),
+                    Style::Hyperlink(Link::Page(Path::new("hey").to_owned(), None)),
                 ),
                 Markdown::new(
                     "a".to_string(),
-                    Style::Hyperlink(Path::new("hey").to_owned()),
+                    Style::Hyperlink(Link::Page(Path::new("hey").to_owned(), None)),
                 ),
                 Markdown::new("b".to_string(), Style::Italic),
                 Markdown::new("hello".to_string(), Style::Italic),
@@ -303,11 +998,11 @@ mod test {
             vec![
                 Markdown::new(
                     "a".to_string(),
-                    Style::Hyperlink(Path::new("hey").to_owned()),
+                    Style::Hyperlink(Link::Page(Path::new("hey").to_owned(), None)),
                 ),
                 Markdown::new(
                     "a".to_string(),
-                    Style::Hyperlink(Path::new("hey").to_owned()),
+                    Style::Hyperlink(Link::Page(Path::new("hey").to_owned(), None)),
                 ),
                 Markdown::new("bhello".to_string(), Style::Italic),
             ],
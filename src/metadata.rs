@@ -0,0 +1,51 @@
+use crate::context::ParseContext;
+use gm_docs_parser::GmManualMetadata;
+use scraper::{Html, Selector};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Files which some manual drops put their version in.
+const VERSION_FILES: [&str; 3] = ["version.txt", "VERSION", "version"];
+
+/// Fills in everything we can about where this manual came from. The versions are read from the
+/// input tree if possible, but anything given on the command line wins.
+pub fn make_metadata(
+    cx: &ParseContext,
+    manual_version: Option<String>,
+    ide_version: Option<String>,
+    runtime_version: Option<String>,
+) -> GmManualMetadata {
+    GmManualMetadata {
+        manual_version: manual_version.or_else(|| detect_manual_version(cx)),
+        ide_version: ide_version.or_else(|| detect_meta(cx, "ide-version")),
+        runtime_version: runtime_version.or_else(|| detect_meta(cx, "runtime-version")),
+        parser_version: env!("CARGO_PKG_VERSION").to_string(),
+        generated_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|v| v.as_secs())
+            .unwrap_or_default(),
+        input_path: cx.base_path.to_string_lossy().to_string(),
+        link_base: cx.url_base.to_string(),
+    }
+}
+
+fn detect_manual_version(cx: &ParseContext) -> Option<String> {
+    VERSION_FILES
+        .iter()
+        .find_map(|fname| std::fs::read_to_string(cx.base_path.join(fname)).ok())
+        .map(|v| v.trim().to_string())
+        .filter(|v| v.is_empty() == false)
+        .or_else(|| detect_meta(cx, "version"))
+}
+
+/// Reads a `<meta name="..." content="...">` tag out of the manual's index page.
+fn detect_meta(cx: &ParseContext, name: &str) -> Option<String> {
+    let index = crate::encoding::read_page(cx, &cx.base_path.join("index.htm")).ok()?;
+    let doc = Html::parse_document(&index);
+    let selector = Selector::parse(&format!("meta[name=\"{}\"]", name)).unwrap();
+
+    doc.select(&selector)
+        .next()
+        .and_then(|meta| meta.value().attr("content"))
+        .map(|v| v.trim().to_string())
+        .filter(|v| v.is_empty() == false)
+}
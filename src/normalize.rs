@@ -0,0 +1,300 @@
+use crate::{GmBlock, GmInline, GmRichText};
+use serde::{Deserialize, Serialize};
+
+/// How text from the Manual is cleaned up once it's read. Every step is on by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GmNormalization {
+    /// Replaces non-breaking spaces and other Unicode spaces with plain spaces, and Unicode
+    /// line breaks with `\n`.
+    pub unicode_whitespace: bool,
+    /// Replaces curly quotes with straight quotes, so code reads as valid GML.
+    pub smart_quotes: bool,
+    /// Decodes Html entities which were escaped twice in the Manual, like `&amp;lt;`. Code is
+    /// left alone, since entities in code are meant literally.
+    pub entities: bool,
+    /// Removes zero-width spaces, joiners, byte order marks and soft hyphens.
+    pub zero_width: bool,
+    /// Collapses runs of whitespace into a single space, and trims the ends. Code blocks keep
+    /// their whitespace.
+    pub repeated_whitespace: bool,
+}
+
+impl Default for GmNormalization {
+    fn default() -> Self {
+        Self {
+            unicode_whitespace: true,
+            smart_quotes: true,
+            entities: true,
+            zero_width: true,
+            repeated_whitespace: true,
+        }
+    }
+}
+
+impl GmNormalization {
+    /// Leaves text exactly as it is.
+    pub const NONE: GmNormalization = GmNormalization {
+        unicode_whitespace: false,
+        smart_quotes: false,
+        entities: false,
+        zero_width: false,
+        repeated_whitespace: false,
+    };
+
+    /// Cleans up a single piece of text, such as a name or a heading.
+    pub fn normalize(&self, text: &str) -> String {
+        let mut output = self.normalize_characters(text, false);
+        if self.repeated_whitespace {
+            output = collapse_whitespace(&output, &mut true);
+            output.truncate(output.trim_end().len());
+        }
+
+        output
+    }
+
+    /// Cleans up every piece of text in a [`GmRichText`]. Links and image sources are left
+    /// alone, and code blocks only have their characters cleaned up.
+    ///
+    /// [`GmRichText`]: struct.GmRichText.html
+    pub fn normalize_rich_text(&self, text: &mut GmRichText) {
+        for block in text.blocks.iter_mut() {
+            match block {
+                GmBlock::Paragraph { content } | GmBlock::Heading { content, .. } => {
+                    self.normalize_inlines(content)
+                }
+                GmBlock::CodeBlock { code, .. } => *code = self.normalize_characters(code, true),
+                GmBlock::List { items, .. } => {
+                    for item in items.iter_mut() {
+                        self.normalize_rich_text(item);
+                    }
+                }
+                GmBlock::Table { header, rows } => {
+                    for cell in header.iter_mut().chain(rows.iter_mut().flatten()) {
+                        self.normalize_inlines(cell);
+                    }
+                }
+                GmBlock::Note { content } => self.normalize_rich_text(content),
+            }
+        }
+    }
+
+    /// Cleans up a run of inline text. Whitespace is collapsed across the whole run, and moved
+    /// out of formatted inlines into the plain text around them, so `a **b** c` never ends up
+    /// written as `a** b **c`.
+    fn normalize_inlines(&self, content: &mut Vec<GmInline>) {
+        let mut output: Vec<GmInline> = Vec::with_capacity(content.len());
+        let mut after_space = true;
+        let mut pending_space = false;
+
+        for mut inline in std::mem::take(content) {
+            let is_plain = matches!(inline, GmInline::Plain { .. });
+            let is_code = matches!(inline, GmInline::Code { .. });
+            let text = match &mut inline {
+                GmInline::Plain { text }
+                | GmInline::Bold { text }
+                | GmInline::Italic { text }
+                | GmInline::Code { text }
+                | GmInline::EntryLink { text, .. }
+                | GmInline::GlossaryTerm { text, .. }
+                | GmInline::AnchorLink { text, .. }
+                | GmInline::UrlLink { text, .. } => text,
+                GmInline::Image { alt, .. } => alt,
+            };
+            *text = self.normalize_characters(text, is_code);
+
+            if !self.repeated_whitespace {
+                output.push(inline);
+                continue;
+            }
+
+            if is_plain {
+                if pending_space {
+                    text.insert(0, ' ');
+                }
+                *text = collapse_whitespace(text, &mut after_space);
+                pending_space = false;
+                if !text.is_empty() {
+                    output.push(inline);
+                }
+                continue;
+            }
+
+            let leading_space = text.starts_with(char::is_whitespace);
+            pending_space = text.ends_with(char::is_whitespace);
+            *text = collapse_whitespace(text, &mut true);
+            text.truncate(text.trim_end().len());
+
+            if leading_space && !after_space {
+                match output.last_mut() {
+                    Some(GmInline::Plain { text }) => text.push(' '),
+                    _ => output.push(GmInline::Plain {
+                        text: " ".to_string(),
+                    }),
+                }
+            }
+            after_space = false;
+            output.push(inline);
+        }
+
+        if self.repeated_whitespace {
+            if let Some(GmInline::Plain { text }) = output.last_mut() {
+                text.truncate(text.trim_end().len());
+            }
+            output.retain(|inline| !matches!(inline, GmInline::Plain { text } if text.is_empty()));
+        }
+
+        *content = output;
+    }
+
+    /// Runs every step which works on one character at a time. Code was already decoded once
+    /// when the Html was read, and `&lt;` in code is meant literally, so entities are only
+    /// decoded in prose.
+    fn normalize_characters(&self, text: &str, is_code: bool) -> String {
+        let decoded;
+        let text = if self.entities && !is_code {
+            decoded = decode_entities(text);
+            decoded.as_str()
+        } else {
+            text
+        };
+
+        text.chars()
+            .filter_map(|c| match c {
+                '\u{200b}' | '\u{200c}' | '\u{200d}' | '\u{2060}' | '\u{feff}' | '\u{ad}'
+                    if self.zero_width =>
+                {
+                    None
+                }
+                '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}' if self.smart_quotes => {
+                    Some('\'')
+                }
+                '\u{201c}' | '\u{201d}' | '\u{201e}' | '\u{201f}' if self.smart_quotes => Some('"'),
+                '\u{85}' | '\u{2028}' | '\u{2029}' if self.unicode_whitespace => Some('\n'),
+                c if self.unicode_whitespace && c.is_whitespace() && !c.is_ascii() => Some(' '),
+                c => Some(c),
+            })
+            .collect()
+    }
+}
+
+/// Collapses every run of whitespace into a single space. A run right after `after_space` is
+/// dropped entirely, which is how the start of the text is trimmed.
+fn collapse_whitespace(text: &str, after_space: &mut bool) -> String {
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !*after_space {
+                output.push(' ');
+            }
+            *after_space = true;
+        } else {
+            output.push(c);
+            *after_space = false;
+        }
+    }
+
+    output
+}
+
+/// Decodes the named entities the Manual uses, and every numeric entity. Anything else is
+/// left as it is.
+fn decode_entities(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        // entities are short, so we don't look any further for the `;`
+        let entity = rest.as_bytes()[1..]
+            .iter()
+            .take(11)
+            .position(|&b| b == b';')
+            .map(|end| &rest[1..end + 1]);
+        let decoded = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => {
+                let number = entity.strip_prefix('#')?;
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => number.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+
+        match (entity, decoded) {
+            (Some(entity), Some(c)) => {
+                output.push(c);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize() {
+        let normalization = GmNormalization::default();
+        assert_eq!(
+            normalization.normalize(
+                "\u{a0} It\u{2019}s\u{200b}  a \u{201c}test\u{201d}\n &amp;lt; &#65;&#x42; &foo "
+            ),
+            "It's a \"test\" &lt; AB &foo"
+        );
+        assert_eq!(GmNormalization::NONE.normalize(" a\u{a0} "), " a\u{a0} ");
+        assert_eq!(
+            decode_entities(&format!("&{}&amp;", "x".repeat(10_000))),
+            format!("&{}&", "x".repeat(10_000))
+        );
+    }
+
+    #[test]
+    fn rich_text() {
+        let mut text = GmRichText {
+            blocks: vec![
+                GmBlock::Paragraph {
+                    content: vec![
+                        GmInline::Plain {
+                            text: "\u{a0}Call ".to_string(),
+                        },
+                        GmInline::Code {
+                            text: " show(&amp;a) ".to_string(),
+                        },
+                        GmInline::Plain {
+                            text: "\n  now. ".to_string(),
+                        },
+                    ],
+                },
+                GmBlock::CodeBlock {
+                    language: None,
+                    code: "if (a)\n{\n\u{a0}\u{a0}\u{a0}\u{a0}b  = \u{201c}&amp;c\u{201d};\n}"
+                        .to_string(),
+                },
+            ],
+        };
+        GmNormalization::default().normalize_rich_text(&mut text);
+
+        assert_eq!(
+            text.to_markdown(),
+            "Call `show(&amp;a)` now.\n\n```\nif (a)\n{\n    b  = \"&amp;c\";\n}\n```"
+        );
+    }
+}
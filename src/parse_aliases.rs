@@ -0,0 +1,161 @@
+use crate::context::ParseContext;
+use gm_docs_parser::*;
+use log::*;
+use scraper::Html;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+/// GML accepts both the British and American spellings of some of its built-ins, like
+/// `draw_set_colour` and `draw_set_color`, but not of all of them. We only record another
+/// spelling as an alias when the manual documents it: either the helpdocs point both spellings
+/// at the same page, or the entry's page mentions both.
+///
+/// Constructors are left alone, since none of them are named with a spelling which has a
+/// variant, and they have no aliases to look them up by.
+pub fn parse_spelling_aliases(
+    cx: &ParseContext,
+    keywords: &BTreeMap<String, PathBuf>,
+    gm_manual: &mut GmManual,
+) {
+    let mut pages = PageWords::default();
+
+    for function in gm_manual.functions.values_mut() {
+        let documented = documented_spellings(
+            cx,
+            keywords,
+            &mut pages,
+            &function.name,
+            function.source.as_ref(),
+        );
+        function.aliases.extend(documented);
+    }
+
+    for variable in gm_manual.variables.values_mut() {
+        let documented = documented_spellings(
+            cx,
+            keywords,
+            &mut pages,
+            &variable.name,
+            variable.source.as_ref(),
+        );
+        variable.aliases.extend(documented);
+    }
+
+    for constant in gm_manual.constants.values_mut() {
+        let documented = documented_spellings(
+            cx,
+            keywords,
+            &mut pages,
+            &constant.name,
+            constant.source.as_ref(),
+        );
+        constant.aliases.extend(documented);
+    }
+}
+
+/// The other spellings of `name` which the manual documents.
+fn documented_spellings(
+    cx: &ParseContext,
+    keywords: &BTreeMap<String, PathBuf>,
+    pages: &mut PageWords,
+    name: &str,
+    source: Option<&GmSource>,
+) -> BTreeSet<String> {
+    let page = source.map(|source| cx.base_path.join(&source.path));
+
+    alternate_spellings(name)
+        .into_iter()
+        .filter(|spelling| {
+            let keyword_page = keywords.get(spelling);
+            let same_keyword_page = keyword_page.is_some()
+                && (keyword_page == keywords.get(name) || keyword_page == page.as_ref());
+
+            same_keyword_page
+                || page
+                    .as_deref()
+                    .map(|page| pages.mentions(cx, page, spelling))
+                    .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// The text of every page we've read, so pages with many entries are only read once.
+#[derive(Debug, Default)]
+struct PageWords(BTreeMap<PathBuf, String>);
+
+impl PageWords {
+    /// Checks if the page mentions `word` on its own, rather than as part of a longer name.
+    fn mentions(&mut self, cx: &ParseContext, page: &Path, word: &str) -> bool {
+        let text =
+            self.0.entry(page.to_owned()).or_insert_with(|| {
+                match crate::encoding::read_page(cx, page) {
+                    Ok(txt) => Html::parse_document(&txt).root_element().text().collect(),
+                    Err(e) => {
+                        error!("couldn't read {:?} to find its spellings: {}", page, e);
+                        String::new()
+                    }
+                }
+            });
+
+        mentions_word(text, word)
+    }
+}
+
+fn mentions_word(text: &str, word: &str) -> bool {
+    let is_identifier = |c: char| c.is_alphanumeric() || c == '_';
+
+    text.match_indices(word).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + word.len()..].chars().next();
+
+        before.map(is_identifier) != Some(true) && after.map(is_identifier) != Some(true)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn documented_only() {
+        let cx = ParseContext::new(PathBuf::from("manual"), Default::default());
+        let keywords: BTreeMap<String, PathBuf> = [
+            ("draw_set_colour", "manual/draw_set_colour.htm"),
+            ("draw_set_color", "manual/draw_set_colour.htm"),
+            ("colour_get_red", "manual/colour_get_red.htm"),
+            ("color_get_red", "manual/color_get_red.htm"),
+        ]
+        .into_iter()
+        .map(|(keyword, page)| (keyword.to_string(), PathBuf::from(page)))
+        .collect();
+        let mut pages = PageWords::default();
+        pages.0.insert(
+            PathBuf::from("manual/Constants.htm"),
+            "c_gray and c_grey are the same, but c_dkgray has no c_dkgrey_2".to_string(),
+        );
+        let constants_page = GmSource {
+            path: "Constants.htm".to_string(),
+            anchor: None,
+            hash: String::new(),
+        };
+
+        let mut spellings = |name: &str, source: Option<&GmSource>| {
+            documented_spellings(&cx, &keywords, &mut pages, name, source)
+        };
+        assert_eq!(
+            spellings("draw_set_colour", None),
+            BTreeSet::from(["draw_set_color".to_string()])
+        );
+        assert_eq!(spellings("colour_get_red", None), BTreeSet::new());
+        assert_eq!(
+            spellings("c_gray", Some(&constants_page)),
+            BTreeSet::from(["c_grey".to_string()])
+        );
+        assert_eq!(
+            spellings("c_dkgray", Some(&constants_page)),
+            BTreeSet::new()
+        );
+    }
+}
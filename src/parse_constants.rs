@@ -1,23 +1,25 @@
-use crate::{parse_fnames::convert_to_url, Markdown};
+use crate::{context::ParseContext, lang::Headings, Markdown};
 use gm_docs_parser::*;
 use scraper::{ElementRef, Html, Node, Selector};
 use std::{collections::BTreeMap, path::Path};
-use url::Url;
 
 pub fn parse_constants(
+    cx: &ParseContext,
     base_path: &Path,
     constants: &mut BTreeMap<String, GmManualConstant>,
+    enums: &mut BTreeMap<String, GmManualEnum>,
+    headings: &Headings,
 ) -> Result<(), Box<dyn std::error::Error>> {
     for file in std::fs::read_dir(base_path)? {
         let file = file?;
         let file_type = file.file_type()?;
 
         if file_type.is_dir() {
-            parse_constants(&file.path(), constants)?;
+            parse_constants(cx, &file.path(), constants, enums, headings)?;
         } else if file_type.is_file() {
             let path = file.path();
             if path.extension().map(|e| e == "htm").unwrap_or_default() {
-                parse_constant(&file.path(), base_path, constants);
+                parse_constant(cx, &file.path(), base_path, constants, enums, headings);
             }
         }
     }
@@ -26,25 +28,35 @@ pub fn parse_constants(
 }
 
 fn parse_constant(
+    cx: &ParseContext,
     fpath: &Path,
     directory_path: &Path,
     constants: &mut BTreeMap<String, GmManualConstant>,
+    enums: &mut BTreeMap<String, GmManualEnum>,
+    headings: &Headings,
 ) {
-    // println!("constant path: {}", fpath.display());
-    let doc = Html::parse_document(&std::fs::read_to_string(fpath).unwrap());
+    let doc = match crate::encoding::read_page(cx, fpath) {
+        Ok(txt) => Html::parse_document(&txt),
+        Err(e) => {
+            log::error!("couldn't read file {:?}: {}", fpath, e);
+            return;
+        }
+    };
 
     for table in doc.select(&Selector::parse("table").unwrap()) {
-        let link = convert_to_url(fpath);
-
-        parse_inner(table, link, directory_path, constants);
+        parse_inner(cx, table, fpath, directory_path, constants, enums, headings);
     }
 
     fn parse_inner(
+        cx: &ParseContext,
         table: ElementRef,
-        link: Url,
+        fpath: &Path,
         dir: &Path,
         constants: &mut BTreeMap<String, GmManualConstant>,
+        enums: &mut BTreeMap<String, GmManualEnum>,
+        headings: &Headings,
     ) -> Option<()> {
+        let link = cx.convert_to_url(fpath);
         let table_body = table.children().nth(1).unwrap();
 
         let mut trs = table_body.children();
@@ -68,11 +80,9 @@ fn parse_constant(
                 let is_constant = th
                     .first_child()
                     .map(|header_v| {
-                        let mut header = Markdown::convert_to_markdown(dir, &header_v);
+                        let header = Markdown::convert_to_plain_text(cx, dir, &header_v);
 
-                        header.make_ascii_lowercase();
-
-                        header.contains("constant")
+                        Headings::matches(headings.constant, &header)
                     })
                     .unwrap_or_default();
 
@@ -87,9 +97,9 @@ fn parse_constant(
                             if e.name() == "th" {
                                 if let Some(next_header) = sibling
                                     .first_child()
-                                    .map(|v| Markdown::convert_to_markdown(dir, &v))
+                                    .map(|v| Markdown::convert_to_plain_text(cx, dir, &v))
                                 {
-                                    if next_header.to_lowercase().contains("description") {
+                                    if Headings::matches(headings.description, &next_header) {
                                         order.push(Order::Description);
                                     } else {
                                         order.push(Order::Other(next_header));
@@ -116,28 +126,43 @@ fn parse_constant(
                 let mut constant_doc = GmManualConstant {
                     name: String::new(),
                     description: String::new(),
+                    rich_description: None,
+                    descriptions: Default::default(),
                     secondary_descriptors: None,
+                    rich_secondary_descriptors: None,
+                    keywords: Default::default(),
+                    aliases: Default::default(),
+                    kind: GmManualConstantKind::Constant,
+                    images: vec![],
+                    source: None,
                     link: link.clone(),
                 };
 
                 if tr.value().is_element() {
+                    cx.take_images();
                     for td in tr.children() {
                         // there are Text(\n) hiddin in the trs
                         if td.value().is_element() {
-                            let data = Markdown::convert_to_markdown(dir, &td);
+                            let rich_data = Markdown::convert_to_rich_text(cx, dir, &td);
+                            let data = cx.render(&rich_data);
 
                             match order.get(caret) {
                                 Some(Order::Constant) => {
-                                    constant_doc.name = data;
+                                    constant_doc.name = rich_data.to_plain_text();
                                 }
                                 Some(Order::Description) => {
                                     constant_doc.description = data;
+                                    constant_doc.rich_description = Some(rich_data);
                                 }
                                 Some(Order::Other(e)) => {
                                     constant_doc
                                         .secondary_descriptors
                                         .get_or_insert_with(Default::default)
                                         .insert(e.clone(), data);
+                                    constant_doc
+                                        .rich_secondary_descriptors
+                                        .get_or_insert_with(Default::default)
+                                        .insert(e.clone(), rich_data);
                                 }
                                 None => {
                                     log::error!("Failed to parse CONSTANTS at {}", link);
@@ -147,20 +172,15 @@ fn parse_constant(
                         }
                     }
 
-                    // clean up the constant...
-                    if constant_doc.name.starts_with('`') && constant_doc.name.ends_with('`') {
-                        constant_doc.name =
-                            constant_doc.name[1..constant_doc.name.len() - 1].to_owned();
-                    }
-                    if constant_doc.name.starts_with("**") && constant_doc.name.ends_with("**") {
-                        constant_doc.name =
-                            constant_doc.name[2..constant_doc.name.len() - 2].to_owned();
-                    }
-
                     if constant_doc.name.starts_with('\\') {
                         continue;
                     }
 
+                    constant_doc.kind = constant_kind(&constant_doc.name);
+                    constant_doc.images = cx.take_images();
+                    constant_doc.source =
+                        ElementRef::wrap(tr).map(|row| crate::source::source_of(cx, fpath, row));
+
                     if let Some(inner) = &mut constant_doc.secondary_descriptors {
                         *inner = inner
                             .clone()
@@ -168,16 +188,24 @@ fn parse_constant(
                             .filter(|(k, v)| !(k.trim().is_empty() || v.trim().is_empty()))
                             .collect();
 
+                        let rich = constant_doc
+                            .rich_secondary_descriptors
+                            .get_or_insert_with(Default::default);
+                        rich.retain(|k, _| inner.contains_key(k));
+
                         if constant_doc.description.trim().is_empty() && inner.is_empty() == false {
-                            constant_doc.description =
-                                inner.remove(&inner.keys().next().unwrap().clone()).unwrap();
+                            let first = inner.keys().next().unwrap().clone();
+                            constant_doc.description = inner.remove(&first).unwrap();
+                            constant_doc.rich_description = rich.remove(&first);
                         }
 
                         if inner.is_empty() {
                             constant_doc.secondary_descriptors = None;
+                            constant_doc.rich_secondary_descriptors = None;
                         }
                     }
 
+                    add_enum_member(enums, &constant_doc);
                     constants.insert(constant_doc.name.clone(), constant_doc);
                 }
             }
@@ -185,9 +213,106 @@ fn parse_constant(
         Some(())
     }
 }
+/// Adds a constant to its enum if it's an enum member. Constants are added as their rows are
+/// read, so members stay in the order the Manual lists them.
+fn add_enum_member(enums: &mut BTreeMap<String, GmManualEnum>, constant: &GmManualConstant) {
+    if constant.kind != GmManualConstantKind::EnumMember {
+        return;
+    }
+
+    if let Some((enum_name, member)) = constant.name.split_once('.') {
+        let members = &mut enums
+            .entry(enum_name.to_string())
+            .or_insert_with(|| GmManualEnum {
+                name: enum_name.to_string(),
+                members: vec![],
+                link: constant.link.clone(),
+            })
+            .members;
+
+        if members.iter().any(|m| m == member) == false {
+            members.push(member.to_string());
+        }
+    }
+}
+
+fn constant_kind(name: &str) -> GmManualConstantKind {
+    let is_identifier =
+        |v: &str| v.is_empty() == false && v.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    match name.split_once('.') {
+        Some((enum_name, member)) if is_identifier(enum_name) && is_identifier(member) => {
+            GmManualConstantKind::EnumMember
+        }
+        _ if name.starts_with("GM_") => GmManualConstantKind::Macro,
+        _ => GmManualConstantKind::Constant,
+    }
+}
+
 #[derive(Debug)]
 enum Order {
     Constant,
     Description,
     Other(String),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use url::Url;
+
+    #[test]
+    fn constant_kinds() {
+        assert_eq!(
+            constant_kind("AudioEffectType.Reverb1"),
+            GmManualConstantKind::EnumMember
+        );
+        assert_eq!(constant_kind("GM_build_date"), GmManualConstantKind::Macro);
+        assert_eq!(constant_kind("c_red"), GmManualConstantKind::Constant);
+        assert_eq!(constant_kind("pi"), GmManualConstantKind::Constant);
+        assert_eq!(
+            constant_kind("AudioEffectType."),
+            GmManualConstantKind::Constant
+        );
+        assert_eq!(
+            constant_kind("layer_get_id(\"a.b\")"),
+            GmManualConstantKind::Constant
+        );
+    }
+
+    #[test]
+    fn enum_members_in_row_order() {
+        let link = Url::parse("https://manual.yoyogames.com/AudioEffect.htm").unwrap();
+        let mut enums = BTreeMap::new();
+
+        for name in [
+            "AudioEffectType.Reverb1",
+            "AudioEffectType.Delay",
+            "c_red",
+            "AudioEffectType.Bitcrusher",
+            "AudioEffectType.Delay",
+        ] {
+            let constant = GmManualConstant {
+                name: name.to_string(),
+                description: String::new(),
+                rich_description: None,
+                descriptions: Default::default(),
+                secondary_descriptors: None,
+                rich_secondary_descriptors: None,
+                keywords: Default::default(),
+                aliases: Default::default(),
+                kind: constant_kind(name),
+                images: vec![],
+                source: None,
+                link: link.clone(),
+            };
+            add_enum_member(&mut enums, &constant);
+        }
+
+        assert_eq!(enums.len(), 1);
+        assert_eq!(
+            enums["AudioEffectType"].members,
+            ["Reverb1", "Delay", "Bitcrusher"]
+        );
+    }
+}
@@ -0,0 +1,225 @@
+use crate::{context::ParseContext, lang::Headings, Markdown};
+use gm_docs_parser::*;
+use scraper::{ElementRef, Html, Selector};
+use std::{collections::BTreeMap, path::Path};
+use url::Url;
+
+pub fn parse_errors(
+    cx: &ParseContext,
+    base_path: &Path,
+    errors: &mut BTreeMap<String, GmManualError>,
+    headings: &Headings,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for file in std::fs::read_dir(base_path)? {
+        let file = file?;
+        let file_type = file.file_type()?;
+
+        if file_type.is_dir() {
+            parse_errors(cx, &file.path(), errors, headings)?;
+        } else if file_type.is_file() {
+            let path = file.path();
+            // localized manuals keep the English file names, so this works in every language
+            let is_error_page = path
+                .file_name()
+                .map(|f| f.to_string_lossy().to_lowercase().contains("error"))
+                .unwrap_or_default();
+
+            if is_error_page && path.extension().map(|e| e == "htm").unwrap_or_default() {
+                let doc = match crate::encoding::read_page(cx, &path) {
+                    Ok(txt) => Html::parse_document(&txt),
+                    Err(e) => {
+                        log::error!("couldn't read file {:?}: {}", path, e);
+                        continue;
+                    }
+                };
+                let link = cx.convert_to_url(&path);
+
+                for table in doc.select(&Selector::parse("table").unwrap()) {
+                    parse_error_table(cx, table, &link, base_path, errors, headings);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_error_table(
+    cx: &ParseContext,
+    table: ElementRef,
+    link: &Url,
+    dir: &Path,
+    errors: &mut BTreeMap<String, GmManualError>,
+    headings: &Headings,
+) {
+    let row_sel = Selector::parse("tr").unwrap();
+    let cell_sel = Selector::parse("th, td").unwrap();
+
+    let mut rows = table.select(&row_sel);
+    let header: Vec<String> = match rows.next() {
+        Some(header) => header
+            .select(&cell_sel)
+            .map(|cell| cell.text().collect::<String>().to_lowercase())
+            .collect(),
+        None => return,
+    };
+
+    let is_error_table = header
+        .first()
+        .map(|h| Headings::matches(headings.error, h))
+        .unwrap_or_default();
+    if is_error_table == false || header.len() < 2 {
+        return;
+    }
+
+    let explanation_column = header
+        .iter()
+        .position(|h| {
+            Headings::matches(headings.description, h) || Headings::matches(headings.explanation, h)
+        })
+        .unwrap_or(header.len() - 1);
+
+    let category = preceding_heading(cx, table).unwrap_or_default();
+
+    for row in rows {
+        let cells: Vec<ElementRef> = row.select(&cell_sel).collect();
+
+        let pattern = match cells.first() {
+            Some(cell) => cx.normalize(&cell.text().collect::<String>()),
+            None => continue,
+        };
+        if pattern.is_empty() {
+            continue;
+        }
+
+        let rich_explanation = cells
+            .get(explanation_column)
+            .map(|cell| Markdown::convert_to_rich_text(cx, dir, cell));
+        let explanation = rich_explanation
+            .as_ref()
+            .map(|v| cx.render(v))
+            .unwrap_or_default();
+
+        errors.insert(
+            pattern.clone(),
+            GmManualError {
+                pattern,
+                category: category.clone(),
+                explanation: explanation.trim().to_string(),
+                rich_explanation,
+                link: link.clone(),
+            },
+        );
+    }
+}
+
+/// Finds the text of the closest heading before an element, climbing out of any containers
+/// the element is in.
+fn preceding_heading(cx: &ParseContext, element: ElementRef) -> Option<String> {
+    let mut current = Some(*element);
+
+    while let Some(node) = current {
+        for sibling in node.prev_siblings() {
+            if let Some(sibling) = ElementRef::wrap(sibling) {
+                if matches!(sibling.value().name(), "h1" | "h2" | "h3" | "h4") {
+                    let heading = sibling.text().collect::<String>();
+                    return Some(cx.normalize(&heading));
+                }
+            }
+        }
+
+        current = node.parent();
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const ERRORS: &str = "<h1>Error Messages</h1>
+        <h2>Compiler Errors</h2>
+        <div><table><tbody>
+        <tr><th>Error Message</th><th>Meaning</th></tr>
+        <tr><td>Malformed assignment</td><td>An assignment is missing its value.</td></tr>
+        <tr><td></td><td>Nothing to match</td></tr>
+        </tbody></table></div>
+        <h3>Runner Errors</h3>
+        <table><tbody>
+        <tr><th>Error</th><th>Type</th><th>Description</th></tr>
+        <tr><td>DoAdd :: Execution Error</td><td>Fatal</td><td>Two values can't be added.</td></tr>
+        </tbody></table>
+        <table><tbody>
+        <tr><th>Function</th><th>Description</th></tr>
+        <tr><td>show_error</td><td>Shows an error.</td></tr>
+        </tbody></table>
+        <table><tbody>
+        <tr><th>Error</th></tr>
+        <tr><td>Unknown</td></tr>
+        </tbody></table>";
+
+    fn parse(html: &str, headings: &Headings) -> BTreeMap<String, GmManualError> {
+        let cx = ParseContext::new(PathBuf::from("manual"), Default::default());
+        let link = cx.convert_to_url(Path::new("manual/Error_Messages.htm"));
+        let doc = Html::parse_document(html);
+        let mut errors = BTreeMap::new();
+
+        for table in doc.select(&Selector::parse("table").unwrap()) {
+            parse_error_table(
+                &cx,
+                table,
+                &link,
+                Path::new("manual"),
+                &mut errors,
+                headings,
+            );
+        }
+
+        errors
+    }
+
+    #[test]
+    fn error_tables() {
+        let errors = parse(ERRORS, Headings::for_lang("en").unwrap());
+
+        assert_eq!(
+            errors.keys().collect::<Vec<_>>(),
+            ["DoAdd :: Execution Error", "Malformed assignment"]
+        );
+
+        let malformed = &errors["Malformed assignment"];
+        assert_eq!(malformed.category, "Compiler Errors");
+        assert_eq!(malformed.explanation, "An assignment is missing its value.");
+        assert_eq!(
+            malformed.link.as_str(),
+            "https://manual.yoyogames.com/Error_Messages.htm"
+        );
+
+        let runner = &errors["DoAdd :: Execution Error"];
+        assert_eq!(runner.category, "Runner Errors");
+        assert_eq!(runner.explanation, "Two values can't be added.");
+        assert_eq!(
+            runner.rich_explanation.as_ref().map(|v| v.to_plain_text()),
+            Some(runner.explanation.clone())
+        );
+    }
+
+    #[test]
+    fn localized_error_tables() {
+        let errors = parse(
+            "<h2>Erreurs</h2><table><tbody>
+            <tr><th>Message d'erreur</th><th>Type</th><th>Signification</th></tr>
+            <tr><td>Affectation mal formée</td><td>Fatale</td><td>Il manque une valeur.</td></tr>
+            </tbody></table>",
+            Headings::for_lang("fr").unwrap(),
+        );
+
+        assert_eq!(
+            errors["Affectation mal formée"].explanation,
+            "Il manque une valeur."
+        );
+        assert_eq!(errors["Affectation mal formée"].category, "Erreurs");
+    }
+}
@@ -1,23 +1,32 @@
-use crate::{parse_fnames::convert_to_url, Markdown};
-use gm_docs_parser::{GmManualFunction, GmManualFunctionParameter, GmManualVariable};
+use crate::{context::ParseContext, lang::Headings, Markdown};
+use ego_tree::NodeRef;
+use gm_docs_parser::{
+    GmBlock, GmInferenceSource, GmInline, GmManualConstructor, GmManualFunction,
+    GmManualFunctionParameter, GmManualStructMember, GmManualVariable, GmManualVariableOrigin,
+    GmParameterInference, GmRichText,
+};
 use log::*;
 use scraper::{html::Select, Html, Node, Selector};
-use std::ops::Deref;
 use std::path::Path;
 
 #[derive(Debug)]
 pub enum DocEntry {
     Function(GmManualFunction),
     Variable(GmManualVariable),
+    Constructor(GmManualConstructor),
 }
 
-pub fn parse_function_file(fpath: &Path) -> Option<DocEntry> {
+pub fn parse_function_file(
+    cx: &ParseContext,
+    fpath: &Path,
+    headings: &Headings,
+) -> Option<DocEntry> {
     if fpath.ends_with("array_delete.htm") {
         info!("WE'RE HERE");
     }
     trace!("{:?}", fpath);
     let directory = fpath.parent().unwrap();
-    let txt = match std::fs::read_to_string(fpath) {
+    let txt = match crate::encoding::read_page(cx, fpath) {
         Ok(v) => v,
         Err(e) => {
             error!("couldn't find file {:?}: {}", fpath, e);
@@ -25,47 +34,119 @@ pub fn parse_function_file(fpath: &Path) -> Option<DocEntry> {
         }
     };
     let doc = Html::parse_document(&txt);
+    cx.take_images();
     let h1_sel = Selector::parse("h1").unwrap();
     let h4_sel = Selector::parse("h4").unwrap();
 
-    let name_description = parse_name_and_description(&doc, &h1_sel, directory);
+    let name_description = parse_name_and_description(cx, &doc, &h1_sel, directory);
     let mut h4_select = doc.select(&h4_sel);
     let parameters =
-        parse_parameters(&mut h4_select, directory).unwrap_or_else(|| Data::Function {
-            parameters: Default::default(),
-            required_parameters: 0,
-            is_variadic: false,
+        parse_parameters(cx, &mut h4_select, directory, headings).unwrap_or_else(|| {
+            Data::Function {
+                parameters: Default::default(),
+                required_parameters: 0,
+                is_variadic: false,
+                inference: Default::default(),
+            }
         });
-    let returns = parse_returns(&mut h4_select, directory);
-    let example = parse_example(&mut h4_select, directory);
+    let returns = parse_returns(cx, &mut h4_select, directory, headings);
+    let mut example = parse_example(cx, &mut h4_select, directory, headings);
+
+    // constructors often skip the "Returns" header, which would have eaten the example
+    let is_constructor = matches!(parameters, Data::Constructor { .. });
+    if is_constructor && example.is_none() {
+        example = parse_example(cx, &mut doc.select(&h4_sel), directory, headings);
+    }
 
     // did we fuckin nail it?
-    let all_success = name_description.is_some() && example.is_some() && returns.is_some();
+    let all_success =
+        name_description.is_some() && (is_constructor || (example.is_some() && returns.is_some()));
     if all_success {
-        let (name, description) = name_description.unwrap();
-        let link = convert_to_url(fpath);
+        let (name, rich_description) = name_description.unwrap();
+        let description = cx.render(&rich_description);
+        let rich_description = Some(rich_description);
+        let link = cx.convert_to_url(fpath);
+        let members = matches!(parameters, Data::Constructor { .. })
+            .then(|| parse_members(cx, &doc, directory, headings))
+            .unwrap_or_default();
+        let images = cx.take_images();
+
+        // the entry runs from its title to the end of its example, leaving out the page's
+        // navigation and footer
+        let title = *doc.select(&h1_sel).next().unwrap();
+        let last = example
+            .as_ref()
+            .or(returns.as_ref())
+            .map(|(_, last)| *last)
+            .unwrap_or(title);
+        let source = Some(crate::source::source_of_range(cx, fpath, title, last));
+        let rich_example = example.map(|(example, _)| example);
+        let rich_returns = returns.map(|(returns, _)| returns);
+        let example = rich_example.as_ref().map(|v| cx.render(v));
+        let returns = rich_returns.as_ref().map(|v| cx.render(v));
 
         let output = match parameters {
             Data::Function {
                 parameters,
                 required_parameters,
                 is_variadic,
+                inference,
             } => DocEntry::Function(GmManualFunction {
                 name,
                 parameters,
                 is_variadic,
                 required_parameters,
+                inference,
                 example: example.unwrap(),
+                rich_example,
                 description,
+                rich_description,
+                descriptions: Default::default(),
                 returns: returns.unwrap(),
+                rich_returns,
                 link,
+                keywords: Default::default(),
+                aliases: Default::default(),
+                images,
+                source,
             }),
             Data::Variable => DocEntry::Variable(GmManualVariable {
                 name,
                 example: example.unwrap(),
+                rich_example,
                 description,
+                rich_description,
+                descriptions: Default::default(),
                 returns: returns.unwrap(),
+                rich_returns,
+                link,
+                origin: GmManualVariableOrigin::Page,
+                keywords: Default::default(),
+                aliases: Default::default(),
+                images,
+                source,
+            }),
+            Data::Constructor {
+                parameters,
+                required_parameters,
+                is_variadic,
+                inference,
+            } => DocEntry::Constructor(GmManualConstructor {
+                name: name.trim_start_matches("new ").trim().to_string(),
+                parameters,
+                required_parameters,
+                is_variadic,
+                inference,
+                members,
+                example: example.unwrap_or_default(),
+                rich_example,
+                description,
+                rich_description,
+                descriptions: Default::default(),
                 link,
+                keywords: Default::default(),
+                images,
+                source,
             }),
         };
 
@@ -83,10 +164,11 @@ pub fn parse_function_file(fpath: &Path) -> Option<DocEntry> {
 }
 
 fn parse_name_and_description(
+    cx: &ParseContext,
     doc: &Html,
     h1_sel: &Selector,
     dir_path: &Path,
-) -> Option<(String, String)> {
+) -> Option<(String, GmRichText)> {
     let title = doc.select(h1_sel).next()?;
     let f_child = title.first_child()?;
     let name = if f_child.has_children() {
@@ -95,14 +177,13 @@ fn parse_name_and_description(
         f_child.value().as_text()?.to_string()
     };
 
-    // trim that white space...this effects JUST display_set_gui_maximize
-    let name = name.trim().to_string();
+    let name = cx.normalize(&name);
 
     let mut sibling_iterator = title.next_siblings();
     sibling_iterator.next(); // skip over the `\n`
 
     let desc = sibling_iterator.next()?;
-    let description = Markdown::convert_to_markdown(dir_path, &desc);
+    let description = Markdown::convert_to_rich_text(cx, dir_path, &desc);
 
     Some((name, description))
 }
@@ -112,18 +193,29 @@ enum Data {
         parameters: Vec<GmManualFunctionParameter>,
         required_parameters: usize,
         is_variadic: bool,
+        inference: GmParameterInference,
     },
     Variable,
+    Constructor {
+        parameters: Vec<GmManualFunctionParameter>,
+        required_parameters: usize,
+        is_variadic: bool,
+        inference: GmParameterInference,
+    },
 }
 
-fn parse_parameters(select: &mut Select, dir_path: &Path) -> Option<Data> {
+fn parse_parameters(
+    cx: &ParseContext,
+    select: &mut Select,
+    dir_path: &Path,
+    headings: &Headings,
+) -> Option<Data> {
     select
         .find(|v| {
             v.first_child()
                 .map(|child| {
-                    let mut syntax_output = Markdown::convert_to_markdown(dir_path, &child);
-                    syntax_output.make_ascii_lowercase();
-                    syntax_output.contains("syntax")
+                    let syntax_output = Markdown::convert_to_plain_text(cx, dir_path, &child);
+                    Headings::matches(headings.syntax, &syntax_output)
                 })
                 .unwrap_or_default()
         })
@@ -135,12 +227,28 @@ fn parse_parameters(select: &mut Select, dir_path: &Path) -> Option<Data> {
             // parse the signature for optionals...
             let signature = syntax_siblings.next()?;
 
-            let sig = Markdown::convert_to_markdown(dir_path, &signature);
-            let (mut param_guesses, mut variadic, is_function) = parse_signature(&sig);
+            let sig = Markdown::convert_to_plain_text(cx, dir_path, &signature);
+            let Signature {
+                args: mut param_guesses,
+                names,
+                variadic: signature_variadic,
+                is_function,
+            } = parse_signature(&sig);
 
             if is_function == false {
                 return Some(Data::Variable);
             }
+            let is_constructor = is_constructor_signature(&sig);
+
+            // where each optional parameter, and the variadic, was found
+            let mut optional_sources: Vec<Option<GmInferenceSource>> = param_guesses
+                .iter()
+                .map(|&arg| (arg == Arg::Optional).then_some(GmInferenceSource::Signature))
+                .collect();
+            let mut variadic_source =
+                signature_variadic.then_some(GmInferenceSource::Signature);
+            let mut conflicts = vec![];
+            let mut table_variadic = false;
 
             syntax_siblings.next(); // skip newline
 
@@ -172,11 +280,10 @@ fn parse_parameters(select: &mut Select, dir_path: &Path) -> Option<Data> {
                         .map(|th| {
                             th.first_child()
                                 .map(|header_v| {
-                                    let mut header =
-                                        Markdown::convert_to_markdown(dir_path, &header_v);
-                                    header.make_ascii_lowercase();
+                                    let header =
+                                        Markdown::convert_to_plain_text(cx, dir_path, &header_v);
 
-                                    header.contains("argument")
+                                    Headings::matches(headings.argument, &header)
                                 })
                                 .unwrap_or_default()
                         })
@@ -190,33 +297,79 @@ fn parse_parameters(select: &mut Select, dir_path: &Path) -> Option<Data> {
                                 let mut td = tr.children();
                                 td.next(); // newline
                                 gm_parameter.parameter =
-                                    Markdown::convert_to_markdown(dir_path, &td.next()?);
+                                    Markdown::convert_to_plain_text(cx, dir_path, &td.next()?);
 
                                 td.next(); // newline
 
-                                gm_parameter.description =
-                                    Markdown::convert_to_markdown(dir_path, &td.next()?);
-
-                                let is_optional = gm_parameter.parameter.contains("optional")
-                                    || gm_parameter.parameter.contains("Optional")
-                                    || gm_parameter.description.contains("optional")
-                                    || gm_parameter.description.contains("Optional");
-
-                                let is_variadic = (gm_parameter.parameter.contains("..")
-                                    && gm_parameter.parameter.ends_with("..") == false)
-                                    || gm_parameter.description.contains("..")
-                                        && gm_parameter.description.ends_with("..") == false;
-
-                                if is_variadic && variadic == false {
-                                    variadic = true;
+                                let description =
+                                    Markdown::convert_to_rich_text(cx, dir_path, &td.next()?);
+                                gm_parameter.description = cx.render(&description);
+                                gm_parameter.rich_description = Some(description);
+
+                                let optional_source = if Headings::matches(
+                                    headings.optional,
+                                    &gm_parameter.parameter,
+                                ) {
+                                    Some(GmInferenceSource::Table)
+                                } else if Headings::matches(
+                                    headings.optional,
+                                    &gm_parameter.description,
+                                ) {
+                                    Some(GmInferenceSource::DescriptionKeyword)
+                                } else {
+                                    None
+                                };
+
+                                let looks_variadic =
+                                    |txt: &str| txt.contains("..") && txt.ends_with("..") == false;
+                                let is_variadic_source = if looks_variadic(&gm_parameter.parameter)
+                                {
+                                    Some(GmInferenceSource::Table)
+                                } else if looks_variadic(&gm_parameter.description) {
+                                    Some(GmInferenceSource::DescriptionKeyword)
+                                } else {
+                                    None
+                                };
+
+                                if let Some(source) = is_variadic_source {
+                                    table_variadic = true;
+                                    if variadic_source.is_none() {
+                                        variadic_source = Some(source);
+                                        if names.is_empty() == false {
+                                            conflicts.push(format!(
+                                                "the {} says `{}` is variadic, but the signature doesn't",
+                                                describe_source(source),
+                                                gm_parameter.parameter
+                                            ));
+                                        }
+                                    }
                                 }
 
-                                if param_guesses.len() <= parameters.len() {
+                                let index = parameters.len();
+                                if param_guesses.len() <= index {
                                     param_guesses.push(Arg::Required);
+                                    optional_sources.push(None);
                                 }
 
-                                if is_optional {
-                                    param_guesses[parameters.len()] = Arg::Optional;
+                                if let Some(source) = optional_source {
+                                    if param_guesses[index] == Arg::Required {
+                                        if index < names.len() {
+                                            conflicts.push(format!(
+                                                "the {} says `{}` is optional, but the signature says it's required",
+                                                describe_source(source),
+                                                gm_parameter.parameter
+                                            ));
+                                        }
+                                        param_guesses[index] = Arg::Optional;
+                                        optional_sources[index] = Some(source);
+                                    }
+                                } else if param_guesses[index] == Arg::Optional
+                                    && index < names.len()
+                                {
+                                    conflicts.push(format!(
+                                        "the signature says `{}` is optional, but the argument table says it's required",
+                                        gm_parameter.parameter
+                                    ));
                                 }
 
                                 parameters.push(gm_parameter);
@@ -224,29 +377,167 @@ fn parse_parameters(select: &mut Select, dir_path: &Path) -> Option<Data> {
                         }
                     }
 
+                    if contains_argument && signature_variadic && table_variadic == false {
+                        conflicts.push(
+                            "the signature is variadic, but no parameter in the argument table is"
+                                .to_string(),
+                        );
+                    }
+
                     let minimum_parameters = param_guesses
                         .iter()
                         .position(|&v| v == Arg::Optional)
                         .unwrap_or(param_guesses.len());
 
-                    Some(Data::Function {
-                        parameters,
-                        required_parameters: minimum_parameters,
-                        is_variadic: variadic,
+                    let mut inference = GmParameterInference {
+                        required_parameters: optional_sources
+                            .get(minimum_parameters)
+                            .copied()
+                            .flatten()
+                            .unwrap_or_default(),
+                        is_variadic: variadic_source.unwrap_or_default(),
+                        signature: names.clone(),
+                        conflicts: conflicts.clone(),
+                    };
+                    let mismatches = signature_mismatches(&parameters, &inference, headings);
+                    inference.conflicts.extend(mismatches);
+
+                    if is_constructor {
+                        Some(Data::Constructor {
+                            parameters,
+                            required_parameters: minimum_parameters,
+                            is_variadic: variadic_source.is_some(),
+                            inference,
+                        })
+                    } else {
+                        Some(Data::Function {
+                            parameters,
+                            required_parameters: minimum_parameters,
+                            is_variadic: variadic_source.is_some(),
+                            inference,
+                        })
+                    }
+                })
+                .or_else(|| {
+                    // constructors with no arguments don't bother with a table
+                    is_constructor.then(|| Data::Constructor {
+                        parameters: vec![],
+                        required_parameters: 0,
+                        is_variadic: signature_variadic,
+                        inference: GmParameterInference {
+                            is_variadic: variadic_source.unwrap_or_default(),
+                            signature: names.clone(),
+                            ..Default::default()
+                        },
                     })
                 })
         })
 }
 
-fn parse_example(select: &mut Select, dir_path: &Path) -> Option<String> {
+/// Constructors are written like `new AudioEffect(type, params)` in their signature.
+fn is_constructor_signature(sig: &str) -> bool {
+    sig.split('(')
+        .next()
+        .map(|before_args| {
+            before_args
+                .split(|c: char| c.is_whitespace() || c == '`')
+                .any(|word| word == "new")
+        })
+        .unwrap_or_default()
+}
+
+/// Finds the table of struct members on a constructor's page.
+fn parse_members(
+    cx: &ParseContext,
+    doc: &Html,
+    dir_path: &Path,
+    headings: &Headings,
+) -> Vec<GmManualStructMember> {
+    let row_sel = Selector::parse("tr").unwrap();
+    let cell_sel = Selector::parse("th, td").unwrap();
+    let mut members = vec![];
+
+    for table in doc.select(&Selector::parse("table").unwrap()) {
+        let mut rows = table.select(&row_sel);
+        let header: Vec<String> = match rows.next() {
+            Some(header) => header
+                .select(&cell_sel)
+                .map(|cell| cell.text().collect::<String>().to_lowercase())
+                .collect(),
+            None => continue,
+        };
+
+        let is_member_table = header
+            .first()
+            .map(|h| Headings::matches(headings.member, h))
+            .unwrap_or_default();
+        if is_member_table == false {
+            continue;
+        }
+
+        let type_column = header
+            .iter()
+            .position(|h| Headings::matches(headings.data_type, h));
+        let description_column = header
+            .iter()
+            .position(|h| Headings::matches(headings.description, h))
+            .unwrap_or(header.len() - 1);
+
+        for row in rows {
+            let cells: Vec<_> = row.select(&cell_sel).collect();
+            let name = match cells.first() {
+                Some(cell) => cx.normalize(&cell.text().collect::<String>()),
+                None => continue,
+            };
+            if name.is_empty() {
+                continue;
+            }
+
+            let read_column = |column: Option<usize>| {
+                column
+                    .and_then(|i| cells.get(i))
+                    .map(|cell| {
+                        Markdown::convert_to_text(cx, dir_path, cell)
+                            .trim()
+                            .to_string()
+                    })
+                    .unwrap_or_default()
+            };
+
+            let rich_description = Some(description_column)
+                .filter(|&i| i > 0)
+                .and_then(|i| cells.get(i))
+                .map(|cell| Markdown::convert_to_rich_text(cx, dir_path, cell));
+
+            members.push(GmManualStructMember {
+                name,
+                data_type: read_column(type_column),
+                description: rich_description
+                    .as_ref()
+                    .map(|v| cx.render(v).trim().to_string())
+                    .unwrap_or_default(),
+                rich_description,
+            });
+        }
+    }
+
+    members
+}
+
+/// Reads the example, along with the last node of it, which is where the entry ends.
+fn parse_example<'a>(
+    cx: &ParseContext,
+    select: &mut Select<'a, '_>,
+    dir_path: &Path,
+    headings: &Headings,
+) -> Option<(GmRichText, NodeRef<'a, Node>)> {
     select
         .find(|v| {
             v.first_child()
                 .map(|v| {
-                    let mut example_output = Markdown::convert_to_markdown(dir_path, &v);
-                    example_output.make_ascii_lowercase();
+                    let example_output = Markdown::convert_to_plain_text(cx, dir_path, &v);
 
-                    example_output.contains("example")
+                    Headings::matches(headings.example, &example_output)
                 })
                 .unwrap_or_default()
         })
@@ -255,42 +546,48 @@ fn parse_example(select: &mut Select, dir_path: &Path) -> Option<String> {
             example_siblings.next(); // skip newline
 
             let example = example_siblings.next()?;
-            let mut gm_example = Markdown::convert_to_markdown(dir_path, &example);
+            let mut nodes = vec![example];
             for ex in example_siblings {
                 match ex.value() {
-                    Node::Text(txt) => {
-                        if txt.deref().trim().is_empty() {
-                            gm_example.push('\n');
-                        } else {
-                            gm_example.push_str(txt.deref().trim());
-                        }
-                    }
+                    Node::Text(_) => nodes.push(ex),
                     Node::Element(_) => {
-                        let next_one = Markdown::convert_to_markdown(dir_path, &ex);
-
-                        if next_one.trim().is_empty() {
+                        // an empty element, like a `<br>`, ends the example
+                        let has_content = ex.descendants().any(|node| match node.value() {
+                            Node::Text(txt) => txt.trim().is_empty() == false,
+                            Node::Element(e) => e.name() == "img",
+                            _ => false,
+                        });
+                        if has_content == false {
                             break;
                         }
 
-                        gm_example.push_str(&next_one);
+                        nodes.push(ex);
                     }
                     _ => break,
                 }
             }
 
-            Some(gm_example)
+            // loose text between the code is read along with it, so it's escaped like the rest
+            let last = *nodes.last()?;
+            let gm_example = Markdown::convert_siblings_to_rich_text(cx, dir_path, nodes);
+            Some((gm_example, last))
         })
 }
 
-fn parse_returns(select: &mut Select, dir_path: &Path) -> Option<String> {
+/// Reads what's returned, along with the node it was read from.
+fn parse_returns<'a>(
+    cx: &ParseContext,
+    select: &mut Select<'a, '_>,
+    dir_path: &Path,
+    headings: &Headings,
+) -> Option<(GmRichText, NodeRef<'a, Node>)> {
     select
         .find(|v| {
             v.first_child()
                 .map(|v| {
-                    let mut example_output = Markdown::convert_to_markdown(dir_path, &v);
-                    example_output.make_ascii_lowercase();
+                    let example_output = Markdown::convert_to_plain_text(cx, dir_path, &v);
 
-                    example_output.contains("returns")
+                    Headings::matches(headings.returns, &example_output)
                 })
                 .unwrap_or_default()
         })
@@ -299,13 +596,19 @@ fn parse_returns(select: &mut Select, dir_path: &Path) -> Option<String> {
             returns_siblings.next(); // skip newline
             let returns = returns_siblings.next()?;
 
-            let mut output = Markdown::convert_to_markdown(dir_path, &returns);
+            let output = Markdown::convert_to_rich_text(cx, dir_path, &returns);
 
-            if output.starts_with("```\n") && output.ends_with("\n```") {
-                output = output[4..output.len() - 4].to_string();
-            }
+            // a return type on its own is written as code, but it's still just a type
+            let output = match output.blocks.as_slice() {
+                [GmBlock::CodeBlock { code, .. }] => GmRichText {
+                    blocks: vec![GmBlock::Paragraph {
+                        content: vec![GmInline::Plain { text: code.clone() }],
+                    }],
+                },
+                _ => output,
+            };
 
-            Some(output)
+            Some((output, returns))
         })
 }
 
@@ -315,18 +618,32 @@ enum Arg {
     Optional,
 }
 
-fn parse_signature(sig: &str) -> (Vec<Arg>, bool, bool) {
+struct Signature {
+    args: Vec<Arg>,
+    /// The parameters as they're written, including any brackets.
+    names: Vec<String>,
+    variadic: bool,
+    is_function: bool,
+}
+
+fn parse_signature(sig: &str) -> Signature {
     let start = sig.find('(');
     let end = sig.find(')');
 
     let succeeded = start.is_some() && end.is_some();
     if succeeded == false {
-        return (vec![], false, false);
+        return Signature {
+            args: vec![],
+            names: vec![],
+            variadic: false,
+            is_function: false,
+        };
     }
     let start = start.unwrap();
     let end = end.unwrap();
 
     let mut output = vec![];
+    let mut names = vec![];
     let mut variadic = false;
 
     // for no param args
@@ -342,6 +659,7 @@ fn parse_signature(sig: &str) -> (Vec<Arg>, bool, bool) {
                     running_required = Arg::Optional;
                 }
                 output.push(running_required);
+                names.push(param.to_string());
 
                 if param.contains('[') {
                     running_required = Arg::Optional;
@@ -358,5 +676,212 @@ fn parse_signature(sig: &str) -> (Vec<Arg>, bool, bool) {
         }
     }
 
-    (output, variadic, true)
+    Signature {
+        args: output,
+        names,
+        variadic,
+        is_function: true,
+    }
+}
+
+fn describe_source(source: GmInferenceSource) -> &'static str {
+    match source {
+        GmInferenceSource::Signature => "signature",
+        GmInferenceSource::Table => "argument table",
+        GmInferenceSource::DescriptionKeyword => "parameter's description",
+        GmInferenceSource::Assumed => "default",
+    }
+}
+
+/// Compares the parameters written in a signature with the ones in the argument table, and
+/// describes every difference in their count or names.
+fn signature_mismatches(
+    parameters: &[GmManualFunctionParameter],
+    inference: &GmParameterInference,
+    headings: &Headings,
+) -> Vec<String> {
+    let mut mismatches = vec![];
+
+    if inference.signature.len() != parameters.len() {
+        mismatches.push(format!(
+            "the signature has {} parameters, but the table has {}",
+            inference.signature.len(),
+            parameters.len()
+        ));
+    }
+
+    for (i, (in_signature, in_table)) in inference.signature.iter().zip(parameters).enumerate() {
+        if parameter_name(in_signature, headings) != parameter_name(&in_table.parameter, headings) {
+            mismatches.push(format!(
+                "parameter {} is `{}` in the signature, but `{}` in the table",
+                i + 1,
+                in_signature,
+                in_table.parameter
+            ));
+        }
+    }
+
+    mismatches
+}
+
+/// Reduces a parameter to its bare name, so `[value]` in a signature matches
+/// `value [optional]` in a table. Any word marking the parameter as optional, in the language of
+/// the manual, is dropped.
+fn parameter_name(parameter: &str, headings: &Headings) -> String {
+    parameter
+        .to_lowercase()
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| Headings::matches(headings.optional, word) == false)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gm_docs_parser::GmTextFormat;
+
+    #[test]
+    fn signature_names() {
+        let signature = parse_signature("`ds_list_add(id, val1 [, val2, ... max_val])`");
+
+        assert_eq!(
+            signature.args,
+            [Arg::Required, Arg::Required, Arg::Optional, Arg::Optional]
+        );
+        assert_eq!(signature.names[1], "val1 [");
+        assert!(signature.variadic);
+    }
+
+    #[test]
+    fn mismatches() {
+        let parameter = |name: &str| GmManualFunctionParameter {
+            parameter: name.to_string(),
+            description: String::new(),
+            rich_description: None,
+        };
+        let inference = GmParameterInference {
+            signature: vec!["x".to_string(), "[y]".to_string()],
+            ..Default::default()
+        };
+
+        let english = Headings::english();
+        let french = Headings::for_lang("fr").unwrap();
+        let mismatches = |parameters: &[GmManualFunctionParameter], headings: &Headings| {
+            signature_mismatches(parameters, &inference, headings)
+        };
+
+        assert!(mismatches(&[parameter("x"), parameter("y [optional]")], english).is_empty());
+        assert!(mismatches(&[parameter("x"), parameter("y [optionnel]")], french).is_empty());
+        assert!(mismatches(&[parameter("x"), parameter("y (facultatif)")], french).is_empty());
+        assert_eq!(
+            mismatches(&[parameter("x"), parameter("y [optionnel]")], english).len(),
+            1
+        );
+        assert_eq!(
+            mismatches(&[parameter("x"), parameter("z")], english).len(),
+            1
+        );
+        assert_eq!(mismatches(&[parameter("x")], english).len(), 1);
+    }
+
+    fn inference(signature: &str, rows: &[(&str, &str)]) -> GmParameterInference {
+        let rows: String = rows
+            .iter()
+            .map(|(name, description)| {
+                format!("<tr>\n<td>{}</td>\n<td>{}</td>\n</tr>\n", name, description)
+            })
+            .collect();
+        let doc = Html::parse_document(&format!(
+            "<h4>Syntax:</h4>\n<p class=\"code\">{}</p>\n<table>\n<tbody>\n\
+            <tr>\n<th>Argument</th>\n<th>Description</th>\n</tr>\n{}</tbody>\n</table>",
+            signature, rows
+        ));
+        let cx = ParseContext::new(Path::new("manual").to_owned(), Default::default());
+
+        match parse_parameters(
+            &cx,
+            &mut doc.select(&Selector::parse("h4").unwrap()),
+            Path::new("manual"),
+            Headings::english(),
+        ) {
+            Some(Data::Function { inference, .. }) => inference,
+            _ => panic!("`{}` isn't a function", signature),
+        }
+    }
+
+    #[test]
+    fn conflicts() {
+        let agreed = inference(
+            "f(x, [y])",
+            &[("x", "A value."), ("y", "Another value, optional.")],
+        );
+        assert!(agreed.conflicts.is_empty(), "{:?}", agreed.conflicts);
+
+        let table_optional = inference(
+            "f(x, y)",
+            &[("x", "A value."), ("y [optional]", "Another value.")],
+        );
+        assert_eq!(
+            table_optional.conflicts,
+            ["the argument table says `y [optional]` is optional, but the signature says it's required"]
+        );
+
+        let signature_optional =
+            inference("f(x, [y])", &[("x", "A value."), ("y", "Another value.")]);
+        assert_eq!(
+            signature_optional.conflicts,
+            ["the signature says `y` is optional, but the argument table says it's required"]
+        );
+
+        let table_variadic = inference("f(values)", &[("values", "Any values... to add.")]);
+        assert_eq!(
+            table_variadic.conflicts,
+            ["the parameter's description says `values` is variadic, but the signature doesn't"]
+        );
+
+        let signature_variadic = inference("f(x, ...)", &[("x", "A value."), ("etc", "More.")]);
+        assert_eq!(
+            signature_variadic.conflicts,
+            [
+                "the signature is variadic, but no parameter in the argument table is",
+                "parameter 2 is `...` in the signature, but `etc` in the table"
+            ]
+        );
+    }
+
+    #[test]
+    fn escaped_examples() {
+        let doc = Html::parse_document(
+            "<h4>Example:</h4>\n<p class=\"code\">a = b &lt; c;</p>\nthen &lt;b&gt; &amp; c\n<p>after</p>",
+        );
+        let h4_sel = Selector::parse("h4").unwrap();
+        let example = |text_format| {
+            let cx = ParseContext::new(
+                Path::new("manual").to_owned(),
+                crate::context::ParseOptions {
+                    text_format,
+                    ..Default::default()
+                },
+            );
+            parse_example(
+                &cx,
+                &mut doc.select(&h4_sel),
+                Path::new("manual"),
+                Headings::english(),
+            )
+            .map(|(example, _)| cx.render(&example))
+        };
+
+        assert_eq!(
+            example(GmTextFormat::Html).as_deref(),
+            Some(
+                "<pre><code class=\"language-gml\">a = b &lt; c;</code></pre>\
+                <p>then &lt;b&gt; &amp; c</p><p>after</p>"
+            )
+        );
+        assert_eq!(
+            example(GmTextFormat::Markdown).as_deref(),
+            Some("```gml\na = b < c;\n```\n\nthen \\<b> & c\n\nafter")
+        );
+    }
 }
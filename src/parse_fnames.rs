@@ -1,70 +1,180 @@
-use once_cell::sync::Lazy;
+use crate::assets::normalize_path;
+use gm_docs_parser::{GmManual, GmManualConstant};
 use std::{
     collections::{BTreeMap, BTreeSet},
     fs,
     path::{Path, PathBuf},
-    sync::Mutex,
 };
-use url::Url;
 
 const HELPDOCS_PATH: &str = "helpdocs_keywords.json";
 
-static BASE_PATH: Lazy<Mutex<PathBuf>> = Lazy::new(Default::default);
-
-pub fn base_path() -> PathBuf {
-    (*BASE_PATH.lock().unwrap()).clone()
-}
-
-pub fn parse_fnames(dir: PathBuf) -> BTreeSet<PathBuf> {
-    let mut thing = BASE_PATH.lock().unwrap();
-    *thing = dir.clone();
-
+/// Reads every keyword in the helpdocs, and the page it points to. Several keywords will often
+/// point to the same page.
+pub fn parse_keywords(dir: &Path) -> BTreeMap<String, PathBuf> {
     let path = dir.join(Path::new(HELPDOCS_PATH));
 
     let map: BTreeMap<String, PathBuf> =
         serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
 
     map.into_iter()
-        .filter_map(|(name, fpath)| {
-            if name.contains(char::is_uppercase)
-                || fpath
-                    .file_name()
-                    .map(|fname| fname.to_string_lossy().contains(char::is_uppercase))
-                    .unwrap_or_default()
-            {
-                return None;
-            }
-            if fpath
-                .to_string_lossy()
-                .contains("GameMaker_Language/GML_Reference")
-                == false
-            {
-                return None;
-            }
-
+        .map(|(name, fpath)| {
             let mut path = dir.join(&fpath);
             path.set_extension("htm");
 
-            Some(path)
+            (name, path)
         })
         .collect()
 }
 
-pub fn convert_to_url(path_to_strip: &Path) -> Url {
-    let output = path_to_strip
-        .strip_prefix(&*BASE_PATH.lock().unwrap())
-        .unwrap();
+/// Groups the keywords by page, keeping only the pages which document functions, variables
+/// and constructors. Entries are named in lowercase, except for constructors, so a page with
+/// uppercase in its name is an overview, like `Drawing.htm`, unless it's named after one of its
+/// keywords, like `AudioEffect.htm`.
+pub fn reference_pages(
+    keywords: &BTreeMap<String, PathBuf>,
+) -> BTreeMap<PathBuf, BTreeSet<String>> {
+    let mut output: BTreeMap<PathBuf, BTreeSet<String>> = BTreeMap::new();
+
+    for (name, fpath) in keywords {
+        if fpath
+            .to_string_lossy()
+            .contains("GameMaker_Language/GML_Reference")
+            == false
+        {
+            continue;
+        }
+
+        output
+            .entry(fpath.clone())
+            .or_default()
+            .insert(name.clone());
+    }
+
+    output.retain(|fpath, keywords| {
+        let stem = fpath
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default();
 
-    Url::parse(&format!(
-        "https://manual.yoyogames.com/{}",
-        output.to_str().unwrap()
-    ))
-    .unwrap()
+        stem.contains(char::is_uppercase) == false || keywords.contains(stem.as_ref())
+    });
+
+    output
+}
+
+/// Gives every constant the keywords which name it, under its own name or one of its aliases.
+pub fn constant_keywords(
+    keywords: &BTreeMap<String, PathBuf>,
+    constants: &mut BTreeMap<String, GmManualConstant>,
+) {
+    for constant in constants.values_mut() {
+        constant.keywords = std::iter::once(&constant.name)
+            .chain(constant.aliases.iter())
+            .filter(|name| keywords.contains_key(*name))
+            .cloned()
+            .collect();
+    }
+}
+
+/// Finds which entry each reference page documents, so links to those pages can refer to
+/// the entry itself. A page documents an entry if the page is named after one of its keywords.
+/// Pages are keyed by their path in the manual at `base_path`, so translations can share them.
+pub fn entry_pages(
+    base_path: &Path,
+    reference_pages: &BTreeMap<PathBuf, BTreeSet<String>>,
+) -> BTreeMap<PathBuf, String> {
+    let base_path = normalize_path(base_path);
+
+    reference_pages
+        .iter()
+        .filter_map(|(fpath, keywords)| {
+            let stem = fpath.file_stem()?.to_str()?;
+            let relative = normalize_path(fpath)
+                .strip_prefix(&base_path)
+                .ok()?
+                .to_owned();
+
+            keywords
+                .contains(stem)
+                .then(|| (relative, stem.to_string()))
+        })
+        .collect()
+}
+
+/// Finds every keyword which didn't produce an entry in the manual, either under its own name
+/// or as one of the keywords of an entry.
+pub fn unmatched_keywords(
+    keywords: &BTreeMap<String, PathBuf>,
+    gm_manual: &GmManual,
+) -> BTreeSet<String> {
+    let matched: BTreeSet<&String> = gm_manual
+        .functions
+        .values()
+        .flat_map(|v| v.keywords.iter().chain(v.aliases.iter()))
+        .chain(
+            gm_manual
+                .variables
+                .values()
+                .flat_map(|v| v.keywords.iter().chain(v.aliases.iter())),
+        )
+        .chain(
+            gm_manual
+                .constants
+                .values()
+                .flat_map(|v| v.keywords.iter().chain(v.aliases.iter())),
+        )
+        .chain(
+            gm_manual
+                .constructors
+                .values()
+                .flat_map(|v| v.keywords.iter()),
+        )
+        .collect();
+
+    keywords
+        .keys()
+        .filter(|keyword| {
+            matched.contains(keyword) == false
+                && gm_manual.functions.contains_key(*keyword) == false
+                && gm_manual.constructors.contains_key(*keyword) == false
+                && gm_manual.variables.contains_key(*keyword) == false
+                && gm_manual.constants.contains_key(*keyword) == false
+        })
+        .cloned()
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn overview_pages() {
+        let keywords: BTreeMap<String, PathBuf> = [
+            ("AudioEffect", "GML_Reference/Audio/AudioEffect.htm"),
+            (
+                "draw_set_colour",
+                "GML_Reference/Drawing/draw_set_colour.htm",
+            ),
+            ("drawing", "GML_Reference/Drawing/Drawing.htm"),
+            ("GM_build_date", "GML_Overview/Compiler_Directives.htm"),
+        ]
+        .into_iter()
+        .map(|(keyword, page)| {
+            (
+                keyword.to_string(),
+                Path::new("GameMaker_Language").join(page),
+            )
+        })
+        .collect();
+
+        let pages: Vec<_> = reference_pages(&keywords)
+            .into_keys()
+            .filter_map(|page| Some(page.file_stem()?.to_str()?.to_string()))
+            .collect();
+        assert_eq!(pages, ["AudioEffect", "draw_set_colour"]);
+    }
+
     #[test]
     fn convert_back() {
         let path = Path::new(
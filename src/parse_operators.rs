@@ -0,0 +1,328 @@
+use crate::{context::ParseContext, lang::Headings, Markdown};
+use ego_tree::NodeId;
+use gm_docs_parser::*;
+use log::*;
+use scraper::{ElementRef, Html, Selector};
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::Path,
+};
+use url::Url;
+
+/// The overview pages which document operators and accessors, relative to the base path.
+const OPERATOR_PAGES: [&str; 2] = [
+    "GameMaker_Language/GML_Overview/Expressions_And_Operators.htm",
+    "GameMaker_Language/GML_Overview/Accessors.htm",
+];
+
+/// Every operator we look for in the overview pages. The manual doesn't mark these up in
+/// any consistent way, so we go looking for them by symbol instead.
+const OPERATORS: &[(&str, GmManualOperatorKind)] = &[
+    ("+", GmManualOperatorKind::Arithmetic),
+    ("-", GmManualOperatorKind::Arithmetic),
+    ("*", GmManualOperatorKind::Arithmetic),
+    ("/", GmManualOperatorKind::Arithmetic),
+    ("%", GmManualOperatorKind::Arithmetic),
+    ("div", GmManualOperatorKind::Arithmetic),
+    ("mod", GmManualOperatorKind::Arithmetic),
+    ("++", GmManualOperatorKind::Arithmetic),
+    ("--", GmManualOperatorKind::Arithmetic),
+    ("==", GmManualOperatorKind::Comparison),
+    ("!=", GmManualOperatorKind::Comparison),
+    ("<", GmManualOperatorKind::Comparison),
+    (">", GmManualOperatorKind::Comparison),
+    ("<=", GmManualOperatorKind::Comparison),
+    (">=", GmManualOperatorKind::Comparison),
+    ("&&", GmManualOperatorKind::Logical),
+    ("||", GmManualOperatorKind::Logical),
+    ("^^", GmManualOperatorKind::Logical),
+    ("!", GmManualOperatorKind::Logical),
+    ("and", GmManualOperatorKind::Logical),
+    ("or", GmManualOperatorKind::Logical),
+    ("xor", GmManualOperatorKind::Logical),
+    ("not", GmManualOperatorKind::Logical),
+    ("&", GmManualOperatorKind::Bitwise),
+    ("|", GmManualOperatorKind::Bitwise),
+    ("^", GmManualOperatorKind::Bitwise),
+    ("~", GmManualOperatorKind::Bitwise),
+    ("<<", GmManualOperatorKind::Bitwise),
+    (">>", GmManualOperatorKind::Bitwise),
+    ("=", GmManualOperatorKind::Assignment),
+    ("+=", GmManualOperatorKind::Assignment),
+    ("-=", GmManualOperatorKind::Assignment),
+    ("*=", GmManualOperatorKind::Assignment),
+    ("/=", GmManualOperatorKind::Assignment),
+    ("%=", GmManualOperatorKind::Assignment),
+    ("&=", GmManualOperatorKind::Assignment),
+    ("|=", GmManualOperatorKind::Assignment),
+    ("^=", GmManualOperatorKind::Assignment),
+    ("??", GmManualOperatorKind::Nullish),
+    ("??=", GmManualOperatorKind::Nullish),
+    ("?", GmManualOperatorKind::Ternary),
+    ("[|", GmManualOperatorKind::Accessor),
+    ("[?", GmManualOperatorKind::Accessor),
+    ("[#", GmManualOperatorKind::Accessor),
+    ("[@", GmManualOperatorKind::Accessor),
+    ("[$", GmManualOperatorKind::Accessor),
+];
+
+pub fn parse_operators(
+    cx: &ParseContext,
+    base_path: &Path,
+    operators: &mut BTreeMap<String, GmManualOperator>,
+    headings: &Headings,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for page in OPERATOR_PAGES {
+        let fpath = base_path.join(page);
+        if fpath.exists() == false {
+            warn!("couldn't find operator page {:?}", fpath);
+            continue;
+        }
+
+        let doc = match crate::encoding::read_page(cx, &fpath) {
+            Ok(txt) => Html::parse_document(&txt),
+            Err(e) => {
+                error!("couldn't read file {:?}: {}", fpath, e);
+                continue;
+            }
+        };
+        let link = cx.convert_to_url(&fpath);
+        parse_operator_page(
+            cx,
+            &doc,
+            fpath.parent().unwrap(),
+            &link,
+            operators,
+            headings,
+        );
+    }
+
+    for &(symbol, _) in OPERATORS {
+        if operators.contains_key(symbol) == false {
+            warn!("couldn't find operator `{}` in the manual", symbol);
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds every operator on one of the overview pages. Words like `and` and symbols like `-`
+/// are everywhere in prose, so we only look at code spans and the cells of operator tables.
+fn parse_operator_page(
+    cx: &ParseContext,
+    doc: &Html,
+    directory: &Path,
+    link: &Url,
+    operators: &mut BTreeMap<String, GmManualOperator>,
+    headings: &Headings,
+) {
+    let tables = operator_tables(doc, headings);
+    let precedences = parse_precedence_table(&tables);
+    let operator_cells: HashSet<NodeId> = tables
+        .iter()
+        .flat_map(|table| table.rows.iter().map(|row| row[table.operator_column].id()))
+        .collect();
+
+    let block_sel = Selector::parse("h2, h3, h4, p, li, dt, tr").unwrap();
+    let code_sel = Selector::parse("tt, code").unwrap();
+    for block in doc.select(&block_sel) {
+        let mut code_spans: Vec<String> = block
+            .select(&code_sel)
+            .map(|code| code.text().collect())
+            .collect();
+        code_spans.extend(
+            block
+                .children()
+                .filter(|cell| operator_cells.contains(&cell.id()))
+                .filter_map(ElementRef::wrap)
+                .map(|cell| cell.text().collect()),
+        );
+
+        for &(symbol, kind) in OPERATORS {
+            let mentioned = code_spans.iter().any(|code| mentions_symbol(code, symbol));
+            if operators.contains_key(symbol) || mentioned == false {
+                continue;
+            }
+
+            let rich_description = match block.value().name() {
+                // headings don't describe anything -- the paragraph after them does
+                "h2" | "h3" | "h4" => match block.next_siblings().find_map(ElementRef::wrap) {
+                    Some(next) => Markdown::convert_to_rich_text(cx, directory, &next),
+                    None => continue,
+                },
+                _ => Markdown::convert_to_rich_text(cx, directory, &block),
+            };
+            let description = cx.render(&rich_description);
+
+            operators.insert(
+                symbol.to_string(),
+                GmManualOperator {
+                    symbol: symbol.to_string(),
+                    kind,
+                    precedence: precedences.get(symbol).copied(),
+                    description: description.trim().to_string(),
+                    rich_description: Some(rich_description),
+                    link: link.clone(),
+                },
+            );
+        }
+    }
+}
+
+/// Checks if a code span mentions the given operator. Worded operators (like `div`) and
+/// symbolic operators must stand alone, but accessors can be written up against their
+/// operands, like `list[| 0]`.
+fn mentions_symbol(text: &str, symbol: &str) -> bool {
+    if symbol.starts_with('[') {
+        return text.contains(symbol);
+    }
+
+    text.split_whitespace()
+        .map(|token| {
+            token.trim_matches(|c: char| matches!(c, ',' | ';' | ':' | '"' | '\'' | '(' | ')'))
+        })
+        .any(|token| token == symbol)
+}
+
+/// A table which lists operators, with the columns we read.
+struct OperatorTable<'a> {
+    operator_column: usize,
+    precedence_column: Option<usize>,
+    /// The cells of every row after the header. Every row has an operator cell.
+    rows: Vec<Vec<ElementRef<'a>>>,
+}
+
+/// Finds every table which has an operator column in its header.
+fn operator_tables<'a>(doc: &'a Html, headings: &Headings) -> Vec<OperatorTable<'a>> {
+    let tr_sel = Selector::parse("tr").unwrap();
+    let cell_sel = Selector::parse("th, td").unwrap();
+    let mut output = vec![];
+
+    for table in doc.select(&Selector::parse("table").unwrap()) {
+        let mut rows = table.select(&tr_sel);
+        let header: Vec<String> = match rows.next() {
+            Some(header) => header
+                .select(&cell_sel)
+                .map(|cell| cell.text().collect::<String>().to_lowercase())
+                .collect(),
+            None => continue,
+        };
+
+        let operator_column = match header
+            .iter()
+            .position(|h| Headings::matches(headings.operator, h))
+        {
+            Some(v) => v,
+            None => continue,
+        };
+        let rows = rows
+            .map(|row| row.select(&cell_sel).collect::<Vec<_>>())
+            .filter(|cells| cells.len() > operator_column)
+            .collect();
+
+        output.push(OperatorTable {
+            operator_column,
+            precedence_column: header
+                .iter()
+                .position(|h| Headings::matches(headings.precedence, h)),
+            rows,
+        });
+    }
+
+    output
+}
+
+/// Reads the precedence of every operator in the tables which have a precedence column.
+fn parse_precedence_table(tables: &[OperatorTable<'_>]) -> BTreeMap<String, usize> {
+    let mut output = BTreeMap::new();
+
+    for table in tables {
+        let precedence_column = match table.precedence_column {
+            Some(v) => v,
+            None => continue,
+        };
+
+        for row in table.rows.iter() {
+            let symbols: String = row[table.operator_column].text().collect();
+            let precedence = row
+                .get(precedence_column)
+                .and_then(|p| p.text().collect::<String>().trim().parse::<usize>().ok());
+
+            if let Some(precedence) = precedence {
+                for symbol in symbols.split(|c: char| c.is_whitespace() || c == ',') {
+                    if symbol.is_empty() == false {
+                        output.insert(symbol.to_string(), precedence);
+                    }
+                }
+            }
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_symbols() {
+        assert!(mentions_symbol("use the div operator", "div"));
+        assert!(mentions_symbol("&&, ||, ^^ (and, or, xor)", "||"));
+        assert!(mentions_symbol("&&, ||, ^^ (and, or, xor)", "and"));
+        assert!(mentions_symbol("my_list[| 0] = 5;", "[|"));
+
+        assert!(mentions_symbol("a division of two", "div") == false);
+        assert!(mentions_symbol("a ?? b", "?") == false);
+        assert!(mentions_symbol("a += b", "=") == false);
+    }
+
+    fn parse_fragment(html: &str) -> BTreeMap<String, GmManualOperator> {
+        let cx = ParseContext::new(Path::new("manual").to_owned(), Default::default());
+        let link = Url::parse("https://manual.yoyogames.com/").unwrap();
+        let mut operators = BTreeMap::new();
+        parse_operator_page(
+            &cx,
+            &Html::parse_document(html),
+            Path::new("manual"),
+            &link,
+            &mut operators,
+            Headings::english(),
+        );
+
+        operators
+    }
+
+    #[test]
+    fn skips_prose() {
+        let operators = parse_fragment(
+            "<p>An expression can be a number or a variable, and it is not limited to \
+            one line - it can span many.</p>\
+            <ul><li><tt>&amp;&amp;, ||, ^^</tt> (<tt>and</tt>, <tt>or</tt> and <tt>xor</tt>): \
+            Combine boolean values.</li></ul>",
+        );
+
+        assert_eq!(
+            operators.keys().collect::<Vec<_>>(),
+            ["&&", "^^", "and", "or", "xor", "||"]
+        );
+        assert!(operators["or"]
+            .description
+            .ends_with("Combine boolean values."));
+    }
+
+    #[test]
+    fn precedence() {
+        let operators = parse_fragment(
+            "<p>Multiplication happens before addition or subtraction.</p>\
+            <table><tr><th>Operator</th><th>Precedence</th></tr>\
+            <tr><td>*, /</td><td>1</td></tr>\
+            <tr><td>+, -</td><td>2</td></tr></table>",
+        );
+
+        // lower numbers bind tighter, so `a + b * c` is `a + (b * c)`
+        assert!(operators["*"].precedence < operators["+"].precedence);
+        assert_eq!(operators["-"].precedence, Some(2));
+        assert_eq!(operators.get("or"), None);
+    }
+}
@@ -0,0 +1,267 @@
+use crate::{context::ParseContext, lang::Headings, Markdown};
+use gm_docs_parser::*;
+use scraper::{ElementRef, Html, Selector};
+use std::{collections::BTreeMap, path::Path};
+
+pub fn parse_shader_builtins(
+    cx: &ParseContext,
+    base_path: &Path,
+    builtins: &mut BTreeMap<String, GmManualShaderBuiltin>,
+    headings: &Headings,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for file in std::fs::read_dir(base_path)? {
+        let file = file?;
+        let file_type = file.file_type()?;
+
+        if file_type.is_dir() {
+            parse_shader_builtins(cx, &file.path(), builtins, headings)?;
+        } else if file_type.is_file() {
+            let path = file.path();
+            // localized manuals keep the English file names, so this works in every language
+            let is_shader_page = path.to_string_lossy().to_lowercase().contains("shader");
+
+            if is_shader_page && path.extension().map(|e| e == "htm").unwrap_or_default() {
+                parse_shader_page(cx, &path, base_path, builtins, headings);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_shader_page(
+    cx: &ParseContext,
+    fpath: &Path,
+    directory_path: &Path,
+    builtins: &mut BTreeMap<String, GmManualShaderBuiltin>,
+    headings: &Headings,
+) {
+    let doc = match crate::encoding::read_page(cx, fpath) {
+        Ok(txt) => Html::parse_document(&txt),
+        Err(e) => {
+            log::error!("couldn't read file {:?}: {}", fpath, e);
+            return;
+        }
+    };
+    let link = cx.convert_to_url(fpath);
+
+    // the example shaders tell us the types of everything...
+    let mut declarations = BTreeMap::new();
+    for code in doc.select(&Selector::parse("p.code, pre").unwrap()) {
+        let code: String = code.text().collect();
+        declarations.append(&mut parse_declarations(&code));
+    }
+
+    // ...and the tables and lists tell us what they do.
+    let row_sel = Selector::parse("tr").unwrap();
+    let cell_sel = Selector::parse("th, td").unwrap();
+    for table in doc.select(&Selector::parse("table").unwrap()) {
+        let mut rows = table.select(&row_sel);
+        let header: Vec<String> = match rows.next() {
+            Some(header) => header
+                .select(&cell_sel)
+                .map(|cell| cell.text().collect::<String>().to_lowercase())
+                .collect(),
+            None => continue,
+        };
+        let description_column = header
+            .iter()
+            .position(|h| Headings::matches(headings.description, h));
+        let type_column = header
+            .iter()
+            .position(|h| Headings::matches(headings.data_type, h));
+
+        for row in rows {
+            let cells: Vec<ElementRef> = row.select(&cell_sel).collect();
+            let name = match cells.first() {
+                Some(first) => cx.normalize(&first.text().collect::<String>()),
+                None => continue,
+            };
+            if is_shader_builtin(&name) == false || builtins.contains_key(&name) {
+                continue;
+            }
+
+            let description = description_column
+                .or_else(|| cells.len().checked_sub(1).filter(|&i| i > 0))
+                .and_then(|i| cells.get(i))
+                .map(|cell| Markdown::convert_to_rich_text(cx, directory_path, cell));
+            let glsl_type = type_column
+                .and_then(|i| cells.get(i))
+                .map(|cell| cx.normalize(&cell.text().collect::<String>()));
+
+            builtins.insert(
+                name.clone(),
+                make_builtin(cx, name, glsl_type, description, &declarations, &link),
+            );
+        }
+    }
+
+    for item in doc.select(&Selector::parse("li, p").unwrap()) {
+        let text: String = item.text().collect();
+        let name = match text.split_whitespace().next() {
+            Some(first) => first.trim_end_matches([':', ',', '-']),
+            None => continue,
+        };
+
+        if is_shader_builtin(name) && builtins.contains_key(name) == false {
+            let description = Markdown::convert_to_rich_text(cx, directory_path, &item);
+            builtins.insert(
+                name.to_string(),
+                make_builtin(
+                    cx,
+                    name.to_string(),
+                    None,
+                    Some(description),
+                    &declarations,
+                    &link,
+                ),
+            );
+        }
+    }
+}
+
+fn make_builtin(
+    cx: &ParseContext,
+    name: String,
+    glsl_type: Option<String>,
+    rich_description: Option<GmRichText>,
+    declarations: &BTreeMap<String, (GmManualShaderBuiltinKind, String)>,
+    link: &url::Url,
+) -> GmManualShaderBuiltin {
+    let (kind, declared_type) = declarations
+        .get(&name)
+        .cloned()
+        .unwrap_or_else(|| (kind_from_name(&name), String::new()));
+
+    GmManualShaderBuiltin {
+        glsl_type: glsl_type
+            .filter(|t| t.is_empty() == false)
+            .unwrap_or(declared_type),
+        kind,
+        description: rich_description
+            .as_ref()
+            .map(|v| cx.render(v).trim().to_string())
+            .unwrap_or_default(),
+        rich_description,
+        link: link.clone(),
+        name,
+    }
+}
+
+/// GameMaker prefixes all of its shader built-ins, so we can spot them by name alone.
+fn is_shader_builtin(name: &str) -> bool {
+    (name.starts_with("gm_") || name.starts_with("in_") || name.starts_with("MATRIX_"))
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn kind_from_name(name: &str) -> GmManualShaderBuiltinKind {
+    if name.starts_with("in_") {
+        GmManualShaderBuiltinKind::Attribute
+    } else if name.starts_with("MATRIX_") {
+        GmManualShaderBuiltinKind::Constant
+    } else {
+        GmManualShaderBuiltinKind::Uniform
+    }
+}
+
+/// Reads the declarations of built-ins out of shader code, like `attribute vec3 in_Position;`
+/// or `#define MATRIX_VIEW 0`. We work on tokens rather than lines, since the manual's code
+/// blocks frequently lose their line breaks.
+fn parse_declarations(code: &str) -> BTreeMap<String, (GmManualShaderBuiltinKind, String)> {
+    const PRECISIONS: [&str; 3] = ["lowp", "mediump", "highp"];
+
+    let mut output = BTreeMap::new();
+    let mut tokens = code
+        .split(|c: char| c.is_whitespace() || c == ';')
+        .filter(|t| t.is_empty() == false)
+        .peekable();
+
+    while let Some(token) = tokens.next() {
+        let kind = match token {
+            "uniform" => GmManualShaderBuiltinKind::Uniform,
+            "attribute" | "in" => GmManualShaderBuiltinKind::Attribute,
+            "const" => GmManualShaderBuiltinKind::Constant,
+            "#define" => {
+                if let Some(name) = tokens.next().filter(|n| is_shader_builtin(n)) {
+                    let is_int = tokens
+                        .peek()
+                        .map(|v| v.parse::<i64>().is_ok())
+                        .unwrap_or_default();
+
+                    output.insert(
+                        name.to_string(),
+                        (
+                            GmManualShaderBuiltinKind::Constant,
+                            if is_int { "int" } else { "" }.to_string(),
+                        ),
+                    );
+                }
+                continue;
+            }
+            _ => continue,
+        };
+
+        let mut glsl_type = match tokens.next() {
+            Some(t) => t,
+            None => break,
+        };
+        if PRECISIONS.contains(&glsl_type) {
+            glsl_type = match tokens.next() {
+                Some(t) => t,
+                None => break,
+            };
+        }
+
+        if let Some(name) = tokens.next() {
+            let (name, array) = match name.find('[') {
+                Some(i) => (&name[..i], &name[i..]),
+                None => (name, ""),
+            };
+
+            if is_shader_builtin(name) {
+                output.insert(name.to_string(), (kind, format!("{}{}", glsl_type, array)));
+            }
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_declarations() {
+        let decls = parse_declarations(
+            "attribute vec3 in_Position; attribute vec4 in_Colour;\n\
+             uniform mat4 gm_Matrices[MATRIX_MAX];uniform lowp sampler2D gm_BaseTexture;\
+             #define MATRIX_VIEW 0\nvarying vec2 v_vTexcoord;",
+        );
+
+        assert_eq!(
+            decls.get("in_Position"),
+            Some(&(GmManualShaderBuiltinKind::Attribute, "vec3".to_string()))
+        );
+        assert_eq!(
+            decls.get("in_Colour"),
+            Some(&(GmManualShaderBuiltinKind::Attribute, "vec4".to_string()))
+        );
+        assert_eq!(
+            decls.get("gm_Matrices"),
+            Some(&(
+                GmManualShaderBuiltinKind::Uniform,
+                "mat4[MATRIX_MAX]".to_string()
+            ))
+        );
+        assert_eq!(
+            decls.get("gm_BaseTexture"),
+            Some(&(GmManualShaderBuiltinKind::Uniform, "sampler2D".to_string()))
+        );
+        assert_eq!(
+            decls.get("MATRIX_VIEW"),
+            Some(&(GmManualShaderBuiltinKind::Constant, "int".to_string()))
+        );
+        assert_eq!(decls.get("v_vTexcoord"), None);
+    }
+}
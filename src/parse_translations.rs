@@ -0,0 +1,249 @@
+use crate::{
+    context::ParseContext,
+    lang::Headings,
+    parse_constants,
+    parse_file::{self, DocEntry},
+};
+use gm_docs_parser::*;
+use log::*;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+use url::Url;
+
+/// Reads the descriptions out of a localized copy of the manual, and adds them to the entries
+/// we already parsed from the primary manual. Localized manuals share their file layout
+/// and the names of their entries with the primary one, so we match them up by both.
+/// The translation is parsed in a context of its own, so nothing from it, like its images,
+/// leaks into the primary manual.
+pub fn parse_translation(
+    cx: &ParseContext,
+    lang: &str,
+    root: &Path,
+    pages: &BTreeMap<PathBuf, BTreeSet<String>>,
+    gm_manual: &mut GmManual,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // the language was checked when the arguments were parsed
+    let headings = Headings::for_lang(lang).unwrap();
+
+    let translation = cx.for_translation(root);
+
+    // entries are matched up by their links, so we look them up by link rather than by name
+    let functions: BTreeMap<Url, String> = gm_manual
+        .functions
+        .values()
+        .map(|v| (v.link.clone(), v.name.clone()))
+        .collect();
+    let variables: BTreeMap<Url, String> = gm_manual
+        .variables
+        .values()
+        .map(|v| (v.link.clone(), v.name.clone()))
+        .collect();
+    let constructors: BTreeMap<Url, String> = gm_manual
+        .constructors
+        .values()
+        .map(|v| (v.link.clone(), v.name.clone()))
+        .collect();
+
+    for page in pages.keys() {
+        let translated_page = match page.strip_prefix(&cx.base_path) {
+            Ok(relative) => root.join(relative),
+            Err(_) => continue,
+        };
+        if translated_page.exists() == false {
+            debug!("no `{}` translation of {:?}", lang, page);
+            continue;
+        }
+
+        let (name, description, descriptions) =
+            match parse_file::parse_function_file(&translation, &translated_page, headings) {
+                Some(DocEntry::Function(v)) => (
+                    v.name,
+                    v.description,
+                    functions
+                        .get(&v.link)
+                        .and_then(|name| gm_manual.functions.get_mut(name))
+                        .map(|f| &mut f.descriptions),
+                ),
+                Some(DocEntry::Variable(v)) => (
+                    v.name,
+                    v.description,
+                    variables
+                        .get(&v.link)
+                        .and_then(|name| gm_manual.variables.get_mut(name))
+                        .map(|f| &mut f.descriptions),
+                ),
+                Some(DocEntry::Constructor(v)) => (
+                    v.name,
+                    v.description,
+                    constructors
+                        .get(&v.link)
+                        .and_then(|name| gm_manual.constructors.get_mut(name))
+                        .map(|f| &mut f.descriptions),
+                ),
+                None => continue,
+            };
+
+        match descriptions {
+            Some(descriptions) => {
+                descriptions.insert(lang.to_string(), description);
+            }
+            None => warn!("`{}` entry {} has no primary entry", lang, name),
+        }
+    }
+
+    let mut constants = BTreeMap::new();
+    // the primary manual already has the enums, and their members' names aren't translated
+    parse_constants::parse_constants(
+        &translation,
+        root,
+        &mut constants,
+        &mut BTreeMap::new(),
+        headings,
+    )?;
+    for (name, constant) in constants {
+        if let Some(primary) = gm_manual.constants.get_mut(&name) {
+            primary
+                .descriptions
+                .insert(lang.to_string(), constant.description);
+        }
+    }
+
+    cx.diagnostics
+        .borrow_mut()
+        .append(translation.diagnostics.into_inner());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = "<html><body>
+<h1>draw_set_colour</h1>
+<p>{description}</p>
+<h4>{syntax}:</h4>
+<p class=\"code\">draw_set_colour(col);</p>
+<table>
+<tbody>
+<tr>
+<th>Argument</th>
+<th>Description</th>
+</tr>
+<tr>
+<td>col</td>
+<td>{argument}</td>
+</tr>
+</tbody>
+</table>
+<h4>{returns}:</h4>
+<p class=\"code\">N/A</p>
+<h4>{example}:</h4>
+<p class=\"code\">draw_set_colour(c_red);</p>
+</body></html>";
+
+    const CONSTANTS: &str = "<html><body><h1>Constants</h1>
+<table>
+<tbody>
+<tr>
+<th>{constant}</th>
+<th>Description</th>
+</tr>
+<tr>
+<td>c_red</td>
+<td>{red}</td>
+</tr>
+</tbody>
+</table>
+</body></html>";
+
+    fn write_manual(root: &Path, words: &[(&str, &str)]) {
+        let fill = |template: &str| {
+            words
+                .iter()
+                .fold(template.to_string(), |page, (key, word)| {
+                    page.replace(&format!("{{{}}}", key), word)
+                })
+        };
+
+        std::fs::create_dir_all(root.join("Drawing")).unwrap();
+        std::fs::write(root.join("Drawing/draw_set_colour.htm"), fill(PAGE)).unwrap();
+        std::fs::write(root.join("Constants.htm"), fill(CONSTANTS)).unwrap();
+    }
+
+    #[test]
+    fn translated_descriptions() {
+        let root = std::env::temp_dir().join(format!(
+            "gm-docs-parser-translations-{}",
+            std::process::id()
+        ));
+        let primary_root = root.join("en");
+        let french_root = root.join("fr");
+        write_manual(
+            &primary_root,
+            &[
+                ("description", "Sets the colour."),
+                ("syntax", "Syntax"),
+                ("argument", "The colour"),
+                ("returns", "Returns"),
+                ("example", "Example"),
+                ("constant", "Constant"),
+                ("red", "Red"),
+            ],
+        );
+        write_manual(
+            &french_root,
+            &[
+                (
+                    "description",
+                    "Définit la couleur, voir <a href=\"draw_set_colour.htm\">draw_set_colour</a>.",
+                ),
+                ("syntax", "Syntaxe"),
+                ("argument", "La couleur"),
+                ("returns", "Renvoie"),
+                ("example", "Exemple"),
+                ("constant", "Constante"),
+                ("red", "Rouge"),
+            ],
+        );
+
+        let page = primary_root.join("Drawing/draw_set_colour.htm");
+        let pages = BTreeMap::from([(
+            page.clone(),
+            BTreeSet::from(["draw_set_colour".to_string()]),
+        )]);
+        let mut cx = ParseContext::new(primary_root.clone(), Default::default());
+        cx.entry_pages = crate::parse_fnames::entry_pages(&primary_root, &pages);
+
+        let mut gm_manual = GmManual::default();
+        match parse_file::parse_function_file(&cx, &page, Headings::english()) {
+            Some(DocEntry::Function(v)) => {
+                gm_manual.functions.insert(v.name.clone(), v);
+            }
+            other => panic!("expected a function, but got {:?}", other),
+        }
+        parse_constants::parse_constants(
+            &cx,
+            &primary_root,
+            &mut gm_manual.constants,
+            &mut gm_manual.enums,
+            Headings::english(),
+        )
+        .unwrap();
+
+        parse_translation(&cx, "fr", &french_root, &pages, &mut gm_manual).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            gm_manual.functions["draw_set_colour"].descriptions["fr"],
+            "Définit la couleur, voir [draw_set_colour](gml:draw_set_colour)."
+        );
+        assert_eq!(gm_manual.constants["c_red"].descriptions["fr"], "Rouge");
+        assert_eq!(
+            gm_manual.functions["draw_set_colour"].description,
+            "Sets the colour."
+        );
+    }
+}
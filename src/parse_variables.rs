@@ -0,0 +1,245 @@
+use crate::{context::ParseContext, lang::Headings, Markdown};
+use gm_docs_parser::*;
+use scraper::{ElementRef, Html, Selector};
+use std::{collections::BTreeMap, path::Path};
+use url::Url;
+
+/// Finds variables which are only documented in tables on overview pages. Variables which
+/// already have a page of their own are left alone, and so are the tables on reference pages,
+/// which list the members of a struct the entry returns rather than any variables.
+pub fn parse_variable_tables(
+    cx: &ParseContext,
+    base_path: &Path,
+    variables: &mut BTreeMap<String, GmManualVariable>,
+    headings: &Headings,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for file in std::fs::read_dir(base_path)? {
+        let file = file?;
+        let file_type = file.file_type()?;
+
+        if file_type.is_dir() {
+            parse_variable_tables(cx, &file.path(), variables, headings)?;
+        } else if file_type.is_file() {
+            let path = file.path();
+            let is_page = path.extension().map(|e| e == "htm").unwrap_or_default();
+            if is_page && cx.entry_for_page(&path).is_none() {
+                let doc = match crate::encoding::read_page(cx, &path) {
+                    Ok(txt) => Html::parse_document(&txt),
+                    Err(e) => {
+                        log::error!("couldn't read file {:?}: {}", path, e);
+                        continue;
+                    }
+                };
+                let link = cx.convert_to_url(&path);
+
+                for table in doc.select(&Selector::parse("table").unwrap()) {
+                    parse_variable_table(cx, table, &path, &link, base_path, variables, headings);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_variable_table(
+    cx: &ParseContext,
+    table: ElementRef,
+    fpath: &Path,
+    link: &Url,
+    dir: &Path,
+    variables: &mut BTreeMap<String, GmManualVariable>,
+    headings: &Headings,
+) {
+    let row_sel = Selector::parse("tr").unwrap();
+    let cell_sel = Selector::parse("th, td").unwrap();
+
+    let mut rows = table.select(&row_sel);
+    let header: Vec<String> = match rows.next() {
+        Some(header) => header
+            .select(&cell_sel)
+            .map(|cell| {
+                cx.normalize(&cell.text().collect::<String>())
+                    .to_lowercase()
+            })
+            .collect(),
+        None => return,
+    };
+
+    let is_variable_table = header
+        .first()
+        .map(|h| Headings::is_exactly(headings.variable, h))
+        .unwrap_or_default();
+    let description_column = header
+        .iter()
+        .position(|h| Headings::matches(headings.description, h));
+    if is_variable_table == false || description_column.is_none() {
+        return;
+    }
+
+    let type_column = header.iter().position(|h| {
+        Headings::matches(headings.data_type, h) || Headings::matches(headings.returns, h)
+    });
+
+    for row in rows {
+        let cells: Vec<ElementRef> = row.select(&cell_sel).collect();
+        let name = match cells
+            .first()
+            .and_then(|c| variable_name(&cx.normalize(&c.text().collect::<String>())))
+        {
+            Some(name) => name,
+            None => continue,
+        };
+
+        if variables.contains_key(&name) {
+            continue;
+        }
+
+        cx.take_images();
+        let read_column = |column: Option<usize>| {
+            column
+                .and_then(|i| cells.get(i))
+                .map(|cell| Markdown::convert_to_rich_text(cx, dir, cell))
+        };
+        let render = |text: &Option<GmRichText>| {
+            text.as_ref()
+                .map(|v| cx.render(v).trim().to_string())
+                .unwrap_or_default()
+        };
+
+        let rich_description = read_column(description_column);
+        let description = render(&rich_description);
+        let rich_returns = read_column(type_column);
+        let returns = render(&rich_returns);
+
+        variables.insert(
+            name.clone(),
+            GmManualVariable {
+                name,
+                example: String::new(),
+                rich_example: None,
+                description,
+                rich_description,
+                descriptions: Default::default(),
+                returns,
+                rich_returns,
+                link: link.clone(),
+                origin: GmManualVariableOrigin::OverviewTable,
+                keywords: Default::default(),
+                aliases: Default::default(),
+                images: cx.take_images(),
+                source: Some(crate::source::source_of(cx, fpath, row)),
+            },
+        );
+    }
+}
+
+/// Cleans up a variable name from a table cell. Arrays are often written with their
+/// indices, like `alarm[0...11]`, so we drop those.
+fn variable_name(cell: &str) -> Option<String> {
+    let cell = cell.trim();
+    let name = cell.split('[').next()?.trim();
+
+    let is_identifier = name
+        .chars()
+        .next()
+        .map(|c| c.is_ascii_alphabetic() || c == '_')
+        .unwrap_or_default()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if is_identifier {
+        Some(name.to_string())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const OVERVIEW: &str = "<h1>Instance Variables</h1>
+        <table><tbody>
+        <tr><th>Variable</th><th>Type</th><th>Description</th></tr>
+        <tr><td>x</td><td>Real</td><td>The x position</td></tr>
+        <tr><td>alarm[0...11]</td><td>Array</td><td>The alarms</td></tr>
+        <tr><td>image_index</td><td>Real</td><td>The frame of the sprite</td></tr>
+        <tr><td>See below</td><td></td><td>Not a variable</td></tr>
+        </tbody></table>
+        <table><tbody>
+        <tr><th>Variable Functions</th><th>Description</th></tr>
+        <tr><td>variable_instance_get</td><td>Gets a variable</td></tr>
+        </tbody></table>
+        <table><tbody>
+        <tr><th>Variable</th><th>Value</th></tr>
+        <tr><td>depth</td><td>0</td></tr>
+        </tbody></table>";
+
+    fn parse(variables: &mut BTreeMap<String, GmManualVariable>) {
+        let cx = ParseContext::new(PathBuf::from("manual"), Default::default());
+        let fpath = Path::new("manual/Instance_Variables.htm");
+        let link = cx.convert_to_url(fpath);
+        let doc = Html::parse_document(OVERVIEW);
+
+        for table in doc.select(&Selector::parse("table").unwrap()) {
+            parse_variable_table(
+                &cx,
+                table,
+                fpath,
+                &link,
+                Path::new("manual"),
+                variables,
+                Headings::english(),
+            );
+        }
+    }
+
+    #[test]
+    fn variable_tables() {
+        let mut variables = BTreeMap::new();
+        parse(&mut variables);
+
+        assert_eq!(
+            variables.keys().collect::<Vec<_>>(),
+            ["alarm", "image_index", "x"]
+        );
+
+        let x = &variables["x"];
+        assert_eq!(x.returns, "Real");
+        assert_eq!(
+            x.rich_returns.as_ref().map(|v| v.to_plain_text()),
+            Some("Real".to_string())
+        );
+        assert_eq!(x.description, "The x position");
+        assert_eq!(x.example, "");
+        assert_eq!(x.origin, GmManualVariableOrigin::OverviewTable);
+        assert_eq!(
+            x.link.as_str(),
+            "https://manual.yoyogames.com/Instance_Variables.htm"
+        );
+        assert!(x.source.is_some());
+    }
+
+    #[test]
+    fn keeps_own_pages() {
+        let mut variables = BTreeMap::new();
+        parse(&mut variables);
+        let mut own_page = variables["x"].clone();
+        own_page.example = "x = 10;".to_string();
+
+        let mut variables = BTreeMap::from([("x".to_string(), own_page.clone())]);
+        parse(&mut variables);
+
+        assert_eq!(variables["x"], own_page);
+        assert!(variables.contains_key("alarm"));
+    }
+
+    #[test]
+    fn variable_names() {
+        assert_eq!(variable_name(" alarm[0...11] ").as_deref(), Some("alarm"));
+        assert_eq!(variable_name("_private").as_deref(), Some("_private"));
+        assert_eq!(variable_name("See below"), None);
+        assert_eq!(variable_name("2d"), None);
+    }
+}
@@ -0,0 +1,838 @@
+use crate::{RELATIVE_LINK_BASE, RELATIVE_LINK_SCHEME};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use url::Url;
+
+/// The scheme of the symbolic links which an [`EntryLink`] is written as in Markdown, such as
+/// `gml:ds_list_add`. Consumers can route these to their own page for the entry.
+///
+/// [`EntryLink`]: enum.GmInline.html#variant.EntryLink
+pub const ENTRY_LINK_SCHEME: &str = "gml";
+
+/// The scheme of the symbolic links which a [`GlossaryTerm`] is written as in Markdown, such as
+/// `glossary:colour`. The rest of the link is the term's key in the glossary.
+///
+/// [`GlossaryTerm`]: enum.GmInline.html#variant.GlossaryTerm
+pub const GLOSSARY_LINK_SCHEME: &str = "glossary";
+
+/// Reads the entry out of a symbolic link, such as `ds_list_add` from `gml:ds_list_add`.
+/// Links to an anchor on the entry's page, like `gml:ds_list_add#arguments`, keep the anchor.
+pub fn parse_entry_link(href: &str) -> Option<(&str, Option<&str>)> {
+    let link = href.strip_prefix(ENTRY_LINK_SCHEME)?.strip_prefix(':')?;
+    let (entry, anchor) = match link.split_once('#') {
+        Some((entry, anchor)) => (entry, Some(anchor)),
+        None => (link, None),
+    };
+
+    (!entry.is_empty()).then_some((entry, anchor))
+}
+
+/// Text from the Manual, kept as a document rather than pre-rendered Markdown, so it can be
+/// rendered however a consumer needs with [`to_markdown`], [`to_plain_text`] or [`to_html`].
+///
+/// [`to_markdown`]: #method.to_markdown
+/// [`to_plain_text`]: #method.to_plain_text
+/// [`to_html`]: #method.to_html
+#[derive(Debug, Clone, PartialEq, Eq, Ord, PartialOrd, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GmRichText {
+    /// The blocks of the text, in order.
+    pub blocks: Vec<GmBlock>,
+}
+
+/// A block of [`GmRichText`].
+///
+/// [`GmRichText`]: struct.GmRichText.html
+#[derive(Debug, Clone, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum GmBlock {
+    /// A run of inline text.
+    Paragraph {
+        /// The text of the paragraph.
+        content: Vec<GmInline>,
+    },
+    /// A heading within the text.
+    Heading {
+        /// The level of the heading, from 1 to 6, like Html's `h1` to `h6`.
+        level: u8,
+        /// The text of the heading.
+        content: Vec<GmInline>,
+    },
+    /// A block of code, such as an example.
+    CodeBlock {
+        /// The language of the code, such as `gml`, if it's known.
+        language: Option<String>,
+        /// The code itself.
+        code: String,
+    },
+    /// A bulleted or numbered list.
+    List {
+        /// If the list is numbered.
+        ordered: bool,
+        /// Each item of the list, which can hold further blocks, including other lists.
+        items: Vec<GmRichText>,
+    },
+    /// A table, such as a list of options and what they mean.
+    Table {
+        /// The header cells. This may be empty if the table has no header.
+        header: Vec<Vec<GmInline>>,
+        /// The cells of each row.
+        rows: Vec<Vec<Vec<GmInline>>>,
+    },
+    /// A note or warning set apart from the rest of the text.
+    Note {
+        /// The text of the note.
+        content: GmRichText,
+    },
+}
+
+/// A run of inline text within a [`GmBlock`].
+///
+/// [`GmBlock`]: enum.GmBlock.html
+#[derive(Debug, Clone, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum GmInline {
+    /// Unstyled text.
+    Plain {
+        /// The text.
+        text: String,
+    },
+    /// Bold text.
+    Bold {
+        /// The text.
+        text: String,
+    },
+    /// Italic text.
+    Italic {
+        /// The text.
+        text: String,
+    },
+    /// Inline code, such as the name of a function.
+    Code {
+        /// The code.
+        text: String,
+    },
+    /// A link to the page of another entry in the Manual. In Markdown, this is written as a
+    /// symbolic link, like `gml:ds_list_add`; see [`ENTRY_LINK_SCHEME`].
+    ///
+    /// [`ENTRY_LINK_SCHEME`]: constant.ENTRY_LINK_SCHEME.html
+    EntryLink {
+        /// The text of the link.
+        text: String,
+        /// The name of the entry linked to, such as `ds_list_add`.
+        entry: String,
+        /// The link to the entry's webpage.
+        url: Url,
+    },
+    /// A term which the Manual defines in a tooltip. In Markdown, this is written as a symbolic
+    /// link, like `glossary:colour`; see [`GLOSSARY_LINK_SCHEME`].
+    ///
+    /// [`GLOSSARY_LINK_SCHEME`]: constant.GLOSSARY_LINK_SCHEME.html
+    GlossaryTerm {
+        /// The text of the term.
+        text: String,
+        /// The term's key in the Manual's `glossary`, which holds its definition.
+        term: String,
+    },
+    /// A link to an anchor on the same page.
+    AnchorLink {
+        /// The text of the link.
+        text: String,
+        /// The `id` or `name` of the element linked to, without the `#`.
+        anchor: String,
+    },
+    /// A link to any other webpage, or to a file in the Manual which isn't a page.
+    UrlLink {
+        /// The text of the link.
+        text: String,
+        /// Where the link goes.
+        url: Url,
+    },
+    /// An image. Its caption, if it has one, is in the entry's `images`.
+    Image {
+        /// The alt text of the image.
+        alt: String,
+        /// The link to the image. See [`GmImage`].
+        ///
+        /// [`GmImage`]: struct.GmImage.html
+        src: String,
+    },
+}
+
+impl GmRichText {
+    /// Checks if the text has no blocks.
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Renders the text as Markdown.
+    pub fn to_markdown(&self) -> String {
+        self.render(GmTextFormat::Markdown)
+    }
+
+    /// Renders the text with all formatting stripped, for consumers which can't show any.
+    pub fn to_plain_text(&self) -> String {
+        self.render(GmTextFormat::Plain)
+    }
+
+    /// Renders the text as Html. All text is escaped, and only simple formatting tags are used.
+    pub fn to_html(&self) -> String {
+        self.render(GmTextFormat::Html)
+    }
+
+    /// Renders the text in any format.
+    pub fn render(&self, format: GmTextFormat) -> String {
+        self.render_under(format, None)
+    }
+
+    /// Renders the text in any format, trusting every link under `link_base` as well, such as
+    /// `file:` links into a local mirror of the Manual, which Html would otherwise leave out.
+    pub fn render_with_link_base(&self, format: GmTextFormat, link_base: &Url) -> String {
+        self.render_under(format, Some(link_base))
+    }
+
+    fn render_under(&self, format: GmTextFormat, link_base: Option<&Url>) -> String {
+        let separator = match format {
+            GmTextFormat::Html => "",
+            GmTextFormat::Markdown | GmTextFormat::Plain => "\n\n",
+        };
+
+        self.blocks
+            .iter()
+            .map(|block| block.render(format, link_base))
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+}
+
+/// The formats which [`GmRichText`] can be rendered as.
+///
+/// [`GmRichText`]: struct.GmRichText.html
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash, Default, Serialize, Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum GmTextFormat {
+    /// CommonMark, with GitHub-flavoured tables.
+    #[default]
+    Markdown,
+    /// Text with no formatting at all.
+    Plain,
+    /// Html using only simple formatting tags, with every link checked to be safe.
+    Html,
+}
+
+impl GmBlock {
+    fn render(&self, format: GmTextFormat, link_base: Option<&Url>) -> String {
+        match self {
+            GmBlock::Paragraph { content } => match format {
+                GmTextFormat::Html => {
+                    format!("<p>{}</p>", render_inlines(content, format, link_base))
+                }
+                GmTextFormat::Markdown | GmTextFormat::Plain => {
+                    render_inlines(content, format, link_base)
+                }
+            },
+            GmBlock::Heading { level, content } => match format {
+                GmTextFormat::Markdown => format!(
+                    "{} {}",
+                    "#".repeat(*level as usize),
+                    render_inlines(content, format, link_base)
+                ),
+                GmTextFormat::Plain => render_inlines(content, format, link_base),
+                GmTextFormat::Html => format!(
+                    "<h{}>{}</h{}>",
+                    level,
+                    render_inlines(content, format, link_base),
+                    level
+                ),
+            },
+            GmBlock::CodeBlock { language, code } => match format {
+                GmTextFormat::Markdown => {
+                    let fence = "`".repeat(longest_backtick_run(code).max(2) + 1);
+                    format!(
+                        "{}{}\n{}\n{}",
+                        fence,
+                        language.as_deref().unwrap_or_default(),
+                        code,
+                        fence
+                    )
+                }
+                GmTextFormat::Plain => code.clone(),
+                GmTextFormat::Html => match language {
+                    Some(language) => format!(
+                        "<pre><code class=\"language-{}\">{}</code></pre>",
+                        escape_html(language),
+                        escape_html(code)
+                    ),
+                    None => format!("<pre><code>{}</code></pre>", escape_html(code)),
+                },
+            },
+            GmBlock::List { ordered, items } => {
+                if format == GmTextFormat::Html {
+                    let tag = if *ordered { "ol" } else { "ul" };
+                    let mut output = format!("<{}>", tag);
+                    for item in items {
+                        write!(output, "<li>{}</li>", item.render_under(format, link_base))
+                            .unwrap();
+                    }
+                    write!(output, "</{}>", tag).unwrap();
+                    return output;
+                }
+
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        let marker = if *ordered {
+                            format!("{}. ", i + 1)
+                        } else {
+                            "- ".to_string()
+                        };
+                        let indent = " ".repeat(marker.len());
+
+                        let mut output = marker;
+                        for (j, line) in item.render_under(format, link_base).lines().enumerate() {
+                            if j > 0 {
+                                output.push('\n');
+                                if !line.is_empty() {
+                                    output.push_str(&indent);
+                                }
+                            }
+                            output.push_str(line);
+                        }
+                        output
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+            GmBlock::Table { header, rows } => match format {
+                GmTextFormat::Markdown => {
+                    let columns = header
+                        .len()
+                        .max(rows.iter().map(|row| row.len()).max().unwrap_or_default());
+                    let table_row = |cells: &[Vec<GmInline>]| {
+                        let mut output = String::from("|");
+                        for i in 0..columns {
+                            let cell = cells
+                                .get(i)
+                                .map(|cell| render_inlines(cell, format, link_base))
+                                .unwrap_or_default();
+                            write!(output, " {} |", table_cell(&cell)).unwrap();
+                        }
+                        output
+                    };
+
+                    let mut lines = vec![table_row(header)];
+                    lines.push(format!("|{}", " --- |".repeat(columns)));
+                    lines.extend(rows.iter().map(|row| table_row(row)));
+                    lines.join("\n")
+                }
+                GmTextFormat::Plain => std::iter::once(header)
+                    .filter(|header| !header.is_empty())
+                    .chain(rows)
+                    .map(|row| {
+                        row.iter()
+                            .map(|cell| render_inlines(cell, format, link_base))
+                            .collect::<Vec<_>>()
+                            .join(" | ")
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                GmTextFormat::Html => {
+                    let mut output = String::from("<table>");
+                    if !header.is_empty() {
+                        output.push_str("<thead><tr>");
+                        for cell in header {
+                            write!(
+                                output,
+                                "<th>{}</th>",
+                                render_inlines(cell, format, link_base)
+                            )
+                            .unwrap();
+                        }
+                        output.push_str("</tr></thead>");
+                    }
+                    output.push_str("<tbody>");
+                    for row in rows {
+                        output.push_str("<tr>");
+                        for cell in row {
+                            write!(
+                                output,
+                                "<td>{}</td>",
+                                render_inlines(cell, format, link_base)
+                            )
+                            .unwrap();
+                        }
+                        output.push_str("</tr>");
+                    }
+                    output.push_str("</tbody></table>");
+                    output
+                }
+            },
+            GmBlock::Note { content } => match format {
+                GmTextFormat::Markdown => content
+                    .render(format)
+                    .lines()
+                    .map(|line| {
+                        if line.is_empty() {
+                            ">".to_string()
+                        } else {
+                            format!("> {}", line)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                GmTextFormat::Plain => content.render_under(format, link_base),
+                GmTextFormat::Html => {
+                    format!(
+                        "<blockquote>{}</blockquote>",
+                        content.render_under(format, link_base)
+                    )
+                }
+            },
+        }
+    }
+}
+
+impl GmInline {
+    fn render(&self, format: GmTextFormat, link_base: Option<&Url>) -> String {
+        match format {
+            GmTextFormat::Markdown => match self {
+                GmInline::Plain { text } => escape_markdown(text),
+                GmInline::Bold { text } => emphasis("**", text),
+                GmInline::Italic { text } => emphasis("*", text),
+                GmInline::Code { text } => code_span(text),
+                GmInline::EntryLink { text, entry, url } => {
+                    let destination = match url.fragment() {
+                        Some(fragment) => format!("{}:{}#{}", ENTRY_LINK_SCHEME, entry, fragment),
+                        None => format!("{}:{}", ENTRY_LINK_SCHEME, entry),
+                    };
+                    format!(
+                        "[{}]({})",
+                        escape_markdown(text),
+                        escape_destination(&destination)
+                    )
+                }
+                GmInline::GlossaryTerm { text, term } => format!(
+                    "[{}]({})",
+                    escape_markdown(text),
+                    escape_destination(&format!("{}:{}", GLOSSARY_LINK_SCHEME, term))
+                ),
+                GmInline::AnchorLink { text, anchor } => format!(
+                    "[{}]({})",
+                    escape_markdown(text),
+                    escape_destination(&format!("#{}", anchor))
+                ),
+                GmInline::UrlLink { text, url } => format!(
+                    "[{}]({})",
+                    escape_markdown(text),
+                    escape_destination(link_destination(url.as_str()))
+                ),
+                GmInline::Image { alt, src } => format!(
+                    "![{}]({})",
+                    escape_markdown(alt),
+                    escape_destination(link_destination(src))
+                ),
+            },
+            GmTextFormat::Plain => match self {
+                GmInline::Plain { text }
+                | GmInline::Bold { text }
+                | GmInline::Italic { text }
+                | GmInline::Code { text }
+                | GmInline::EntryLink { text, .. }
+                | GmInline::GlossaryTerm { text, .. }
+                | GmInline::AnchorLink { text, .. }
+                | GmInline::UrlLink { text, .. } => text.clone(),
+                GmInline::Image { alt, .. } => alt.clone(),
+            },
+            GmTextFormat::Html => match self {
+                GmInline::Plain { text } => escape_html(text),
+                GmInline::Bold { text } => format!("<strong>{}</strong>", escape_html(text)),
+                GmInline::Italic { text } => format!("<em>{}</em>", escape_html(text)),
+                GmInline::Code { text } => format!("<code>{}</code>", escape_html(text)),
+                GmInline::EntryLink { text, url, .. } | GmInline::UrlLink { text, url } => {
+                    if is_safe_link(url, link_base) {
+                        format!(
+                            "<a href=\"{}\">{}</a>",
+                            escape_html(link_destination(url.as_str())),
+                            escape_html(text)
+                        )
+                    } else {
+                        escape_html(text)
+                    }
+                }
+                GmInline::GlossaryTerm { text, term } => format!(
+                    "<dfn data-term=\"{}\">{}</dfn>",
+                    escape_html(term),
+                    escape_html(text)
+                ),
+                GmInline::AnchorLink { text, anchor } => format!(
+                    "<a href=\"#{}\">{}</a>",
+                    escape_html(anchor),
+                    escape_html(text)
+                ),
+                GmInline::Image { alt, src } => {
+                    // exported images are relative paths, which are always safe
+                    let is_safe = Url::parse(src)
+                        .map(|url| is_safe_link(&url, link_base))
+                        .unwrap_or(true);
+                    if is_safe {
+                        format!(
+                            "<img src=\"{}\" alt=\"{}\">",
+                            escape_html(link_destination(src)),
+                            escape_html(alt)
+                        )
+                    } else {
+                        escape_html(alt)
+                    }
+                }
+            },
+        }
+    }
+}
+
+fn render_inlines(inlines: &[GmInline], format: GmTextFormat, link_base: Option<&Url>) -> String {
+    let mut output = String::new();
+    for inline in inlines {
+        let rendered = inline.render(format, link_base);
+
+        // a `!` at the end of one inline would make a link in the next into an image
+        if format == GmTextFormat::Markdown && rendered.starts_with('[') {
+            if let Some(before) = output.strip_suffix('!') {
+                let backslashes = before.len() - before.trim_end_matches('\\').len();
+                if backslashes % 2 == 0 {
+                    output.insert(before.len(), '\\');
+                }
+            }
+        }
+        output.push_str(&rendered);
+    }
+
+    output
+}
+
+/// Escapes text so CommonMark reads it back exactly. Characters are only escaped where they
+/// could mean something, so `ds_list_add` and `a && b` stay readable.
+fn escape_markdown(txt: &str) -> String {
+    let mut output = String::with_capacity(txt.len());
+
+    for line in txt.split_inclusive('\n') {
+        let content_start = line.len() - line.trim_start_matches([' ', '\t']).len();
+        let content = &line[content_start..];
+
+        // the character which would start a block, like the `.` of `1.`
+        let digits = content.len()
+            - content
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .len();
+        let marker = if digits > 0 {
+            content[digits..]
+                .starts_with(['.', ')'])
+                .then_some(content_start + digits)
+        } else {
+            content
+                .starts_with(['#', '=', '+', '-', '>'])
+                .then_some(content_start)
+        };
+
+        let chars: Vec<(usize, char)> = line.char_indices().collect();
+        for (j, &(i, c)) in chars.iter().enumerate() {
+            let prev = j.checked_sub(1).map(|j| chars[j].1);
+            let next = chars.get(j + 1).map(|&(_, c)| c);
+            let rest = &line[i + c.len_utf8()..];
+
+            let is_special = match c {
+                '`' | '*' | '[' | '~' => true,
+                // a `!` before a link makes it an image
+                '!' => next == Some('['),
+                '\\' => next.map(|c| c.is_ascii_punctuation()).unwrap_or_default(),
+                // underscores inside of words never make emphasis
+                '_' => {
+                    let is_word =
+                        |c: Option<char>| c.map(|c| c.is_alphanumeric()).unwrap_or_default();
+                    !(is_word(prev) && is_word(next))
+                }
+                // these only matter when they start a tag or an autolink
+                '<' => next
+                    .map(|c| c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?'))
+                    .unwrap_or_default(),
+                // and this only when it starts an entity, like `&amp;`
+                '&' => {
+                    let entity = rest.trim_start_matches('#');
+                    let name = entity.trim_start_matches(|c: char| c.is_ascii_alphanumeric());
+                    name.len() < entity.len() && name.starts_with(';')
+                }
+                _ => false,
+            };
+            if is_special || marker == Some(i) {
+                output.push('\\');
+            }
+            output.push(c);
+        }
+    }
+
+    output
+}
+
+/// Emphasis can't start or end with whitespace, so any whitespace is moved outside of it.
+fn emphasis(marker: &str, txt: &str) -> String {
+    let inner = txt.trim();
+    if inner.is_empty() {
+        return txt.to_string();
+    }
+
+    let start = txt.len() - txt.trim_start().len();
+    let end = txt.trim_end().len();
+    format!(
+        "{}{}{}{}{}",
+        &txt[..start],
+        marker,
+        escape_markdown(inner),
+        marker,
+        &txt[end..]
+    )
+}
+
+/// Code spans can't be escaped, so they're fenced by more backticks than they hold.
+fn code_span(code: &str) -> String {
+    let fence = "`".repeat(longest_backtick_run(code) + 1);
+    let needs_padding = code.starts_with('`')
+        || code.ends_with('`')
+        || (code.starts_with(' ') && code.ends_with(' ') && !code.trim().is_empty());
+    let padding = if needs_padding { " " } else { "" };
+
+    format!("{}{}{}{}{}", fence, padding, code, padding, fence)
+}
+
+fn longest_backtick_run(txt: &str) -> usize {
+    txt.split(|c| c != '`')
+        .map(|run| run.len())
+        .max()
+        .unwrap_or_default()
+}
+
+fn escape_destination(destination: &str) -> String {
+    let mut output = String::with_capacity(destination.len());
+    for c in destination.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                output.push('\\');
+                output.push(c);
+            }
+            ' ' => output.push_str("%20"),
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+/// Markdown table cells have to be on one line, and can't have bare pipes.
+fn table_cell(cell: &str) -> String {
+    cell.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('|', "\\|")
+}
+
+/// Only links to webpages and email addresses, and links into the Manual, are written into
+/// Html, so nothing like a `javascript:` link can sneak through.
+fn is_safe_link(url: &Url, link_base: Option<&Url>) -> bool {
+    let in_manual = link_base
+        .map(|base| url.as_str().starts_with(base.as_str()))
+        .unwrap_or_default();
+
+    in_manual
+        || matches!(
+            url.scheme(),
+            "http" | "https" | "mailto" | RELATIVE_LINK_SCHEME
+        )
+}
+
+/// Where a link is written to go. Links relative to the Manual are written as relative paths,
+/// and every other link is written as it is.
+fn link_destination(link: &str) -> &str {
+    link.strip_prefix(RELATIVE_LINK_BASE).unwrap_or(link)
+}
+
+fn escape_html(txt: &str) -> String {
+    let mut output = String::with_capacity(txt.len());
+    for c in txt.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&#39;"),
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(text: &str) -> GmInline {
+        GmInline::Plain {
+            text: text.to_string(),
+        }
+    }
+
+    fn paragraph(content: Vec<GmInline>) -> GmRichText {
+        GmRichText {
+            blocks: vec![GmBlock::Paragraph { content }],
+        }
+    }
+
+    #[test]
+    fn renderers() {
+        let text = GmRichText {
+            blocks: vec![
+                GmBlock::Paragraph {
+                    content: vec![
+                        plain("Adds "),
+                        GmInline::Code {
+                            text: "a < b".to_string(),
+                        },
+                        plain(" to "),
+                        GmInline::EntryLink {
+                            text: "a list".to_string(),
+                            entry: "ds_list_create".to_string(),
+                            url: Url::parse("https://manual.yoyogames.com/ds_list_create.htm")
+                                .unwrap(),
+                        },
+                    ],
+                },
+                GmBlock::List {
+                    ordered: false,
+                    items: vec![paragraph(vec![plain("one")]), paragraph(vec![plain("two")])],
+                },
+            ],
+        };
+
+        assert_eq!(
+            text.to_markdown(),
+            "Adds `a < b` to [a list](gml:ds_list_create)\n\n- one\n- two"
+        );
+        assert_eq!(text.to_plain_text(), "Adds a < b to a list\n\n- one\n- two");
+        assert_eq!(
+            text.to_html(),
+            "<p>Adds <code>a &lt; b</code> to <a href=\"https://manual.yoyogames.com/ds_list_create.htm\">a list</a></p><ul><li><p>one</p></li><li><p>two</p></li></ul>"
+        );
+    }
+
+    #[test]
+    fn escaping() {
+        let text = paragraph(vec![
+            plain("1. a*b_c [d] <e> & `f`"),
+            plain(" &amp; _g"),
+            GmInline::Bold {
+                text: "bold ".to_string(),
+            },
+            GmInline::Code {
+                text: "`template ${str}`".to_string(),
+            },
+            plain(" Careful!"),
+            GmInline::EntryLink {
+                text: "see".to_string(),
+                entry: "abs".to_string(),
+                url: Url::parse("https://manual.yoyogames.com/abs.htm").unwrap(),
+            },
+            plain(" wow! ![x]"),
+        ]);
+
+        assert_eq!(
+            text.to_markdown(),
+            "1\\. a\\*b_c \\[d] \\<e> & \\`f\\` \\&amp; \\_g**bold** `` `template ${str}` `` \
+             Careful\\![see](gml:abs) wow! \\!\\[x]"
+        );
+        assert_eq!(
+            GmRichText {
+                blocks: vec![GmBlock::CodeBlock {
+                    language: None,
+                    code: "a ```".to_string(),
+                }],
+            }
+            .to_markdown(),
+            "````\na ```\n````"
+        );
+    }
+
+    #[test]
+    fn entry_links() {
+        assert_eq!(
+            parse_entry_link("gml:ds_list_add"),
+            Some(("ds_list_add", None))
+        );
+        assert_eq!(
+            parse_entry_link("gml:ds_list_add#arguments"),
+            Some(("ds_list_add", Some("arguments")))
+        );
+        assert_eq!(parse_entry_link("gml:"), None);
+        assert_eq!(parse_entry_link("https://manual.yoyogames.com/"), None);
+    }
+
+    #[test]
+    fn unsafe_links() {
+        let text = paragraph(vec![
+            GmInline::UrlLink {
+                text: "click".to_string(),
+                url: Url::parse("javascript:alert(1)").unwrap(),
+            },
+            GmInline::Image {
+                alt: "<pic>".to_string(),
+                src: "assets/pic.png".to_string(),
+            },
+        ]);
+
+        assert_eq!(
+            text.render(GmTextFormat::Html),
+            "<p>click<img src=\"assets/pic.png\" alt=\"&lt;pic&gt;\"></p>"
+        );
+    }
+
+    #[test]
+    fn manual_links() {
+        let link = |url: &str| {
+            paragraph(vec![GmInline::UrlLink {
+                text: "page".to_string(),
+                url: Url::parse(url).unwrap(),
+            }])
+        };
+        let mirror = Url::parse("file:///mirror/").unwrap();
+
+        assert_eq!(
+            link("file:///mirror/a.htm").render_with_link_base(GmTextFormat::Html, &mirror),
+            "<p><a href=\"file:///mirror/a.htm\">page</a></p>"
+        );
+        assert_eq!(
+            link("file:///etc/passwd").render_with_link_base(GmTextFormat::Html, &mirror),
+            "<p>page</p>"
+        );
+        assert_eq!(
+            link("manual:///Drawing/a.htm#b").to_html(),
+            "<p><a href=\"Drawing/a.htm#b\">page</a></p>"
+        );
+        assert_eq!(
+            link("manual:///Drawing/a.htm").to_markdown(),
+            "[page](Drawing/a.htm)"
+        );
+    }
+
+    #[test]
+    fn tables() {
+        let table = GmRichText {
+            blocks: vec![GmBlock::Table {
+                header: vec![vec![plain("Value")], vec![plain("Meaning")]],
+                rows: vec![vec![vec![plain("a|b")], vec![plain("either\na or b")]]],
+            }],
+        };
+
+        assert_eq!(
+            table.to_markdown(),
+            "| Value | Meaning |\n| --- | --- |\n| a\\|b | either a or b |"
+        );
+    }
+}
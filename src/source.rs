@@ -0,0 +1,177 @@
+use crate::context::ParseContext;
+use ego_tree::NodeRef;
+use gm_docs_parser::GmSource;
+use scraper::{ElementRef, Node};
+use std::path::Path;
+
+/// Records where an entry was parsed from. The `fragment` is the Html element which the entry
+/// came from, such as a row of a table.
+pub fn source_of(cx: &ParseContext, fpath: &Path, fragment: ElementRef) -> GmSource {
+    source_of_nodes(cx, fpath, &[*fragment])
+}
+
+/// Records where an entry which spans several nodes was parsed from, such as a page's entry,
+/// which runs from its `<h1>` to the end of its example. `last` is lifted up to a sibling of
+/// `first`, so the range never cuts an element in half.
+pub fn source_of_range(
+    cx: &ParseContext,
+    fpath: &Path,
+    first: NodeRef<Node>,
+    last: NodeRef<Node>,
+) -> GmSource {
+    let last = std::iter::once(last)
+        .chain(last.ancestors())
+        .find(|node| node.parent() == first.parent());
+
+    let nodes: Vec<_> = match last {
+        Some(last) => {
+            let mut nodes = vec![first];
+            if last != first {
+                nodes.extend(first.next_siblings().take_while(|node| *node != last));
+                nodes.push(last);
+            }
+            nodes
+        }
+        None => vec![first.parent().unwrap_or(first)],
+    };
+
+    source_of_nodes(cx, fpath, &nodes)
+}
+
+fn source_of_nodes(cx: &ParseContext, fpath: &Path, nodes: &[NodeRef<Node>]) -> GmSource {
+    let path = fpath
+        .strip_prefix(&cx.base_path)
+        .unwrap_or(fpath)
+        .to_string_lossy()
+        .replace('\\', "/");
+    let html: String = nodes.iter().map(|&node| canonical_html(node)).collect();
+
+    GmSource {
+        path,
+        anchor: find_anchor(nodes),
+        hash: format!("{:016x}", fnv1a(html.as_bytes())),
+    }
+}
+
+/// Writes out the fragment's Html with its attributes sorted. Scraper keeps attributes in a
+/// `HashMap`, so its own `html()` can order them differently on every run.
+fn canonical_html(fragment: NodeRef<Node>) -> String {
+    let mut output = String::new();
+
+    for edge in fragment.traverse() {
+        match edge {
+            ego_tree::iter::Edge::Open(node) => match node.value() {
+                Node::Element(element) => {
+                    let mut attrs: Vec<_> = element.attrs().collect();
+                    attrs.sort_unstable();
+
+                    output.push('<');
+                    output.push_str(element.name());
+                    for (name, value) in attrs {
+                        output.push_str(&format!(" {}=\"{}\"", name, value));
+                    }
+                    output.push('>');
+                }
+                Node::Text(txt) => output.push_str(txt),
+                _ => {}
+            },
+            ego_tree::iter::Edge::Close(node) => {
+                if let Some(element) = node.value().as_element() {
+                    output.push_str(&format!("</{}>", element.name()));
+                }
+            }
+        }
+    }
+
+    output
+}
+
+/// Finds the closest `id` or `name` we can link to: the first node's own, then the first one
+/// inside of it, then the closest one around it. Anchors further into the fragment are left
+/// alone, so linking to a page's entry lands on its title rather than on, say, its example.
+fn find_anchor(fragment: &[NodeRef<Node>]) -> Option<String> {
+    let first = fragment.first()?;
+    let anchor_of = |element: ElementRef| {
+        let value = element.value();
+        value
+            .id()
+            .or_else(|| (value.name() == "a").then(|| value.attr("name")).flatten())
+            .map(|v| v.to_string())
+    };
+
+    first
+        .descendants()
+        .chain(first.ancestors())
+        .filter_map(ElementRef::wrap)
+        .find_map(anchor_of)
+}
+
+/// The 64 bit FNV-1a hash. We use this over the std's hasher, which isn't guaranteed to give
+/// the same hash between versions of Rust.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scraper::{Html, Selector};
+
+    #[test]
+    fn anchors() {
+        let doc = Html::parse_fragment(
+            r#"<div id="outer"><table><tr><td><a name="c_red">c_red</a></td></tr><tr><td>c_blue</td></tr></table></div>"#,
+        );
+        let rows: Vec<_> = doc.select(&Selector::parse("tr").unwrap()).collect();
+
+        assert_eq!(find_anchor(&[*rows[0]]).as_deref(), Some("c_red"));
+        assert_eq!(find_anchor(&[*rows[1]]).as_deref(), Some("outer"));
+    }
+
+    #[test]
+    fn page_range() {
+        let cx = ParseContext::new(Path::new("manual").to_owned(), Default::default());
+        let source = |title: &str, footer: &str| {
+            let doc = Html::parse_document(&format!(
+                r#"<div id="nav">Home</div><div id="content">{}<p>Absolute.</p><h4 id="example">Example:</h4><p class="code">abs(-1)</p></div><div id="footer">{}</div>"#,
+                title, footer
+            ));
+            let first = *doc.select(&Selector::parse("h1").unwrap()).next().unwrap();
+            let last = *doc
+                .select(&Selector::parse("p.code").unwrap())
+                .next()
+                .unwrap();
+
+            source_of_range(&cx, Path::new("manual/abs.htm"), first, last)
+        };
+
+        let title = "<h1>abs</h1>";
+        assert_eq!(source(title, "2023").anchor.as_deref(), Some("content"));
+        assert_eq!(source(title, "2023").hash, source(title, "2024").hash);
+        assert_eq!(source(title, "2023").path, "abs.htm");
+
+        let anchored = r#"<h1 id="abs">abs</h1>"#;
+        assert_eq!(source(anchored, "2023").anchor.as_deref(), Some("abs"));
+        let named = r#"<h1><a name="abs_title"></a>abs</h1>"#;
+        assert_eq!(source(named, "2023").anchor.as_deref(), Some("abs_title"));
+    }
+
+    #[test]
+    fn sorted_attributes() {
+        let doc = Html::parse_fragment(r#"<p id="a" class="b" title="c">text</p>"#);
+        let p = doc.select(&Selector::parse("p").unwrap()).next().unwrap();
+
+        assert_eq!(
+            canonical_html(*p),
+            r#"<p class="b" id="a" title="c">text</p>"#
+        );
+    }
+
+    #[test]
+    fn stable_hash() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::context::ParseContext;
+use ego_tree::NodeRef;
+use log::*;
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use scraper::Node;
+
+/// Reads back the Markdown made from some Html, and reports it if its text isn't the same as
+/// the Html's, if we were asked to verify Markdown. Whitespace is ignored, since Markdown and
+/// Html lay it out differently.
+pub fn check(cx: &ParseContext, container: &NodeRef<Node>, markdown: &str) {
+    if cx.options.verify_markdown == false {
+        return;
+    }
+
+    let expected = cx.normalize(&html_text(container));
+    let found = markdown_text(markdown);
+    if strip_whitespace(&expected) == strip_whitespace(&found) {
+        return;
+    }
+
+    warn!(
+        "markdown doesn't match its html\n..html: {:?}\n..markdown: {:?}",
+        expected.trim(),
+        markdown
+    );
+    cx.diagnostics.borrow_mut().verify_failures += 1;
+}
+
+/// The text content of some Html, leaving out the definitions of tooltips, which are moved
+/// into the glossary.
+fn html_text(container: &NodeRef<Node>) -> String {
+    let is_definition = |node: NodeRef<Node>| {
+        node.value()
+            .as_element()
+            .map(|e| e.name() != "a" && e.classes().any(|c| c.starts_with("tooltip")))
+            .unwrap_or_default()
+    };
+
+    container
+        .descendants()
+        .filter(|node| {
+            node.ancestors()
+                .take_while(|a| a.id() != container.id())
+                .any(is_definition)
+                == false
+        })
+        .filter_map(|node| node.value().as_text().map(|txt| txt.to_string()))
+        .collect()
+}
+
+/// The text content of some Markdown, as a CommonMark reader sees it. The alt text of images
+/// is left out, since it isn't part of the Html's text either.
+fn markdown_text(markdown: &str) -> String {
+    let mut output = String::new();
+    let mut in_image = 0;
+
+    for event in Parser::new_ext(markdown, Options::ENABLE_TABLES) {
+        match event {
+            Event::Start(Tag::Image { .. }) => in_image += 1,
+            Event::End(TagEnd::Image) => in_image -= 1,
+            Event::Text(txt) | Event::Code(txt) if in_image == 0 => output.push_str(&txt),
+            Event::SoftBreak | Event::HardBreak => output.push('\n'),
+            _ => {}
+        }
+    }
+
+    output
+}
+
+fn strip_whitespace(txt: &str) -> String {
+    txt.chars().filter(|c| c.is_whitespace() == false).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_back() {
+        assert_eq!(
+            markdown_text("1\\. a\\*b `` `c` `` ![pic](a.png) [d](gml:d)"),
+            "1. a*b `c`  d"
+        );
+    }
+}